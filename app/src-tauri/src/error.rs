@@ -0,0 +1,136 @@
+use reqwest::StatusCode;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Why a price check failed. Sent to the frontend as an object tagged with `kind` so it can
+/// show guidance specific to the failure and offer a retry where one could help.
+#[derive(Debug, Clone, PartialEq, Error, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TradeError {
+    #[error("No results found")]
+    NoResults,
+    #[error("Trade site rate limit reached, try again in {retry_after}s")]
+    RateLimited { retry_after: u64 },
+    #[error("Invalid query: {message}")]
+    InvalidQuery { code: u64, message: String },
+    #[error("Not logged in to the trade site: {message}")]
+    NotLoggedIn { status: u16, message: String },
+    /// Any other error the trade API reported, with its error code if it sent one
+    #[error("Trade site error ({status}): {message}")]
+    Api {
+        code: Option<u64>,
+        status: u16,
+        message: String,
+    },
+    #[error("{request} request failed: {message}")]
+    Network { request: String, message: String },
+    #[error("{request} request timed out")]
+    TimedOut { request: String },
+    /// A newer price check replaced the one this request was for
+    #[error("Search cancelled")]
+    Cancelled,
+    #[error("Unexpected response from the trade site: {message}")]
+    InvalidResponse { message: String },
+    #[error("{message}")]
+    InvalidItemText { message: String },
+    #[error("{message}")]
+    Other { message: String },
+}
+
+// Error codes of the trade API, see https://www.pathofexile.com/developer/docs/index#errors
+const CODE_RATE_LIMITED: u64 = 3;
+const CODE_INVALID_QUERY: u64 = 2;
+const CODE_FORBIDDEN: u64 = 6;
+const CODE_UNAUTHORIZED: u64 = 8;
+const CODE_UNPROCESSABLE: u64 = 10;
+
+impl TradeError {
+    /// Classifies an `{"error": {"code", "message"}}` body or a bare HTTP status from the trade API
+    pub fn from_api(status: StatusCode, code: Option<u64>, message: String) -> Self {
+        match (code, status) {
+            (Some(CODE_RATE_LIMITED), _) | (_, StatusCode::TOO_MANY_REQUESTS) => Self::RateLimited { retry_after: 0 },
+            (Some(code @ (CODE_INVALID_QUERY | CODE_UNPROCESSABLE)), _) => Self::InvalidQuery { code, message },
+            (Some(CODE_FORBIDDEN | CODE_UNAUTHORIZED), _) | (_, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                Self::NotLoggedIn {
+                    status: status.as_u16(),
+                    message,
+                }
+            }
+            _ => Self::Api {
+                code,
+                status: status.as_u16(),
+                message,
+            },
+        }
+    }
+
+    /// Whether the same request could succeed when sent again
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Network { .. } | Self::TimedOut { .. } => true,
+            Self::Api { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    pub fn item_text(message: impl Into<String>) -> Self {
+        Self::InvalidItemText {
+            message: message.into(),
+        }
+    }
+}
+
+// Lets commands keep using `?` on the plain string errors of storage, locks and serde
+impl From<String> for TradeError {
+    fn from(message: String) -> Self {
+        Self::Other { message }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_error_classification() {
+        assert_eq!(
+            TradeError::from_api(StatusCode::BAD_REQUEST, Some(2), "Invalid query".to_string()),
+            TradeError::InvalidQuery {
+                code: 2,
+                message: "Invalid query".to_string()
+            }
+        );
+        assert_eq!(
+            TradeError::from_api(StatusCode::TOO_MANY_REQUESTS, None, String::new()),
+            TradeError::RateLimited { retry_after: 0 }
+        );
+        assert_eq!(
+            TradeError::from_api(StatusCode::FORBIDDEN, Some(6), "Forbidden".to_string()),
+            TradeError::NotLoggedIn {
+                status: 403,
+                message: "Forbidden".to_string()
+            }
+        );
+        assert_eq!(
+            TradeError::from_api(StatusCode::INTERNAL_SERVER_ERROR, Some(4), "Internal error".to_string()),
+            TradeError::Api {
+                code: Some(4),
+                status: 500,
+                message: "Internal error".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_tagged() {
+        let error = TradeError::RateLimited { retry_after: 12 };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({"kind": "rate_limited", "retry_after": 12})
+        );
+        assert_eq!(
+            serde_json::to_value(TradeError::NoResults).unwrap(),
+            serde_json::json!({"kind": "no_results"})
+        );
+    }
+}
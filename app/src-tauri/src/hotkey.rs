@@ -1,7 +1,9 @@
+use crate::error::TradeError;
 use crate::model::trade_filter::TradeFilters;
 use tauri::AppHandle;
 use tauri::Emitter;
 use tauri::Manager;
+use tauri::WebviewWindow;
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
 #[cfg(target_os = "windows")]
@@ -9,9 +11,11 @@ use windows::Win32::UI::Input::KeyboardAndMouse::{
     SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VK_C, VK_CONTROL,
 };
 
+/// Copies the hovered item and shows its filters. `parse_item_text` returns None for items
+/// that can't be parsed yet and were queued, the window is only brought up for those.
 pub fn handle_shortcut(
     app_handle: &AppHandle,
-    parse_item_text: impl Fn(&str) -> Result<TradeFilters, String>,
+    parse_item_text: impl Fn(&str) -> Result<Option<TradeFilters>, TradeError>,
 ) -> Result<(), String> {
     if !is_poe_active() {
         return Ok(());
@@ -46,16 +50,10 @@ pub fn handle_shortcut(
     let filters = parse_item_text(&item_text)
         .map_err(|e| format!("Failed to parse item text: {}", e))?;
 
-    window
-        .emit("parsed_filters", serde_json::to_string(&filters).unwrap())
-        .map_err(|e| format!("Failed to emit parsed filters: {}", e))?;
-
-    // Show window
-    let _ = window.unminimize();
-    std::thread::sleep(std::time::Duration::from_millis(10));
-    let _ = window.show();
-    std::thread::sleep(std::time::Duration::from_millis(10));
-    let _ = window.set_focus();
+    match filters {
+        Some(filters) => emit_filters(&window, &filters)?,
+        None => show_window(&window),
+    }
 
     // Restore previous clipboard content
     window
@@ -66,6 +64,23 @@ pub fn handle_shortcut(
     Ok(())
 }
 
+/// Sends parsed filters to the frontend and brings the window up to show them
+pub fn emit_filters(window: &WebviewWindow, filters: &TradeFilters) -> Result<(), String> {
+    window
+        .emit("parsed_filters", serde_json::to_string(filters).unwrap())
+        .map_err(|e| format!("Failed to emit parsed filters: {}", e))?;
+    show_window(window);
+    Ok(())
+}
+
+fn show_window(window: &WebviewWindow) {
+    let _ = window.unminimize();
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    let _ = window.show();
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    let _ = window.set_focus();
+}
+
 #[cfg(target_os = "windows")]
 fn is_poe_active() -> bool {
     use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowTextW};
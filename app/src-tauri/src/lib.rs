@@ -1,25 +1,36 @@
-use reqwest::Client;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use tauri_plugin_dialog::DialogExt;
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
 use tauri_plugin_opener::OpenerExt;
 
-use crate::mapping::mod_pattern_map::ModPatternMap;
-use crate::mapping::base_type_map::BaseTypeMap;
-use crate::model::trade_filter::TradeFilters;
+use serde::Serialize;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::error::TradeError;
+use crate::mapping::Mappings;
+use crate::model::exchange::ExchangeQuery;
+use crate::model::price_summary::PriceSummary;
+use crate::model::trade_filter::{SortKey, TradeFilters};
 use crate::model::trade_query::TradeQuery;
+use crate::model::trade_result::PriceCheckResult;
+use crate::service::active_check::{cancellable, ActiveCheck};
+use crate::service::blocklist::Blocklist;
+use crate::service::currency_rates::{order_by_normalized_price, CurrencyRateCache, CurrencyRates};
+use crate::service::endpoints::Endpoints;
+use crate::service::live_search::LiveSearchManager;
+use crate::service::mapping_cache::{self, CachedMappings};
+use crate::service::presets::Presets;
+use crate::service::search_cache::{CachedSearch, SearchCache};
 use crate::service::trade_api;
+use crate::service::trade_client::{self, TradeClient};
 
+mod error;
 mod hotkey;
 mod mapping;
 mod model;
 mod service;
 mod tray;
 
-fn create_client() -> Result<Client, reqwest::Error> {
-    Client::builder().user_agent("chiseled-price-checker").build()
-}
-
 fn show_error(app_handle: &tauri::AppHandle, e: String) {
     log::error!("Error: {}", e);
     if let Some(window) = app_handle.get_webview_window("main") {
@@ -32,34 +43,183 @@ fn show_error(app_handle: &tauri::AppHandle, e: String) {
     }
 }
 
+/// Whether the trade data price checks need has loaded, sent to the frontend as `mappings_status`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum MappingsStatus {
+    Loading,
+    Ready,
+    Failed { error: String },
+}
+
+/// Text of an item copied before the trade data loaded, checked as soon as it has
+#[derive(Default)]
+struct PendingItem(Mutex<Option<String>>);
+
+fn set_mappings_status(app_handle: &tauri::AppHandle, status: MappingsStatus) {
+    if let Ok(mut current) = app_handle.state::<Mutex<MappingsStatus>>().lock() {
+        *current = status.clone();
+    }
+    if let Err(e) = app_handle.emit("mappings_status", &status) {
+        log::warn!("Failed to emit mappings status: {}", e);
+    }
+}
+
+/// Runs `f` with the trade mappings, failing with a friendly message while they're still loading
+fn with_mappings<T>(app_handle: &tauri::AppHandle, f: impl FnOnce(&Mappings) -> T) -> Result<T, String> {
+    let state = app_handle.state::<RwLock<Option<Mappings>>>();
+    let mappings = state.read().map_err(|e| format!("Failed to read mappings: {}", e))?;
+    let mappings = mappings
+        .as_ref()
+        .ok_or("Trade data is still loading, try again in a moment")?;
+    Ok(f(mappings))
+}
+
+/// Swaps in new mappings and checks the item copied while they were loading, if any
+fn store_mappings(app_handle: &tauri::AppHandle, mappings: Mappings) -> Result<(), String> {
+    let state = app_handle.state::<RwLock<Option<Mappings>>>();
+    *state.write().map_err(|e| format!("Failed to update mappings: {}", e))? = Some(mappings);
+    set_mappings_status(app_handle, MappingsStatus::Ready);
+
+    let pending = app_handle
+        .state::<PendingItem>()
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read pending item: {}", e))?
+        .take();
+    if let Some(item_text) = pending {
+        let filters = with_mappings(app_handle, |mappings| mappings.item_text_to_trade_filters(&item_text))?
+            .map_err(|e| format!("Failed to parse item text: {}", e))?;
+        app_handle.state::<ActiveCheck>().restart();
+        let window = app_handle
+            .get_webview_window("main")
+            .ok_or("Main window not found")?;
+        hotkey::emit_filters(&window, &filters)?;
+    }
+    Ok(())
+}
+
+/// Fetches fresh trade data and swaps it into the shared mappings if it changed.
+/// Returns whether the mappings were replaced.
+pub(crate) async fn reload_mappings(app_handle: &tauri::AppHandle) -> Result<bool, String> {
+    let fresh = mapping_cache::fetch_and_store(&app_handle.state::<Arc<TradeClient>>()).await?;
+
+    let state = app_handle.state::<RwLock<Option<Mappings>>>();
+    {
+        let mut current = state
+            .write()
+            .map_err(|e| format!("Failed to update mappings: {}", e))?;
+        if let Some(mappings) = current.as_mut().filter(|mappings| mappings.hash == fresh.hash) {
+            // The hash only covers the trade data, a league change alone still has to be
+            // remembered or every later league fetch would trigger another refresh
+            mappings.leagues = fresh.leagues;
+            log::info!("Trade mappings unchanged");
+            return Ok(false);
+        }
+    }
+
+    // Build the new maps before taking the write lock so price checks aren't blocked meanwhile
+    let mappings = Mappings::from_cache(fresh)?;
+    store_mappings(app_handle, mappings)?;
+
+    log::info!("Trade mappings reloaded");
+    Ok(true)
+}
+
+/// Loads the trade data in the background, from the on-disk cache when there is one,
+/// so the window and hotkey are up right away even on a slow connection
+async fn load_mappings(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let cached_mappings = CachedMappings::load().unwrap_or_else(|e| {
+        log::warn!("Failed to load cached trade mappings: {}", e);
+        None
+    });
+
+    match cached_mappings {
+        Some(cached) => {
+            log::info!("Using cached trade mappings from {}s ago", cached.age().as_secs());
+            store_mappings(app_handle, Mappings::from_cache(cached)?)?;
+
+            if let Err(e) = reload_mappings(app_handle).await {
+                log::warn!("Failed to refresh trade mappings: {}", e);
+            }
+        }
+        None => {
+            reload_mappings(app_handle).await.map_err(|e| {
+                format!("Failed to fetch trade site mappings. Are you logged in to the official trade site?\n\nError: {}", e)
+            })?;
+        }
+    }
+    Ok(())
+}
+
 fn setup(app: &mut tauri::App) -> Result<(), String> {
     let app_handle = app.handle().clone();
     let _tray = tray::create_tray_menu(&app_handle);
 
-    let client = create_client().map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let endpoints = Endpoints::load().unwrap_or_else(|e| {
+        log::warn!("Failed to load trade site endpoints, using the official site: {}", e);
+        Endpoints::default()
+    });
+    let client = Arc::new(TradeClient::new(endpoints)?);
+    match trade_client::load_session_id() {
+        Ok(session_id) => client.set_session_id(session_id.as_deref()),
+        Err(e) => log::warn!("Failed to load session id: {}", e),
+    }
+    app.manage(client);
+    app.manage(SearchCache::default());
+    app.manage(CurrencyRateCache::default());
+    app.manage(LiveSearchManager::default());
+    app.manage(ActiveCheck::default());
 
-    let (stats_api_response, items_api_response) = tauri::async_runtime::block_on(
-        trade_api::fetch_mappings(&client)
-    ).map_err(|e| format!("Failed to fetch trade site mappings. Are you logged in to the official trade site?\n\nError: {}", e))?;
+    app.manage(RwLock::new(None::<Mappings>));
+    app.manage(Mutex::new(MappingsStatus::Loading));
+    app.manage(PendingItem::default());
 
-    let mod_pattern_map = ModPatternMap::new(stats_api_response)
-        .map_err(|e| format!("Failed to create mod pattern map: {}", e))?;
-    let base_type_map = BaseTypeMap::new(items_api_response)
-        .map_err(|e| format!("Failed to create base type map: {}", e))?;
+    let loading_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = load_mappings(&loading_handle).await {
+            log::error!("Failed to load trade mappings: {}", e);
+            set_mappings_status(&loading_handle, MappingsStatus::Failed { error: e });
+        }
+    });
 
-    let item_text_to_trade_filters = move |text: &str| -> Result<TradeFilters, String> {
-        TradeFilters::from_text(
-            |text, prefix| mod_pattern_map.mod_pattern_to_trade_stat(text, prefix),
-            |text| base_type_map.item_text_to_base_type(text),
-            text,
-        )
-    };
+    let blocklist = Blocklist::load().unwrap_or_else(|e| {
+        log::warn!("Failed to load blocked sellers: {}", e);
+        Blocklist::default()
+    });
+    app.manage(Mutex::new(blocklist));
+
+    let presets = Presets::load().unwrap_or_else(|e| {
+        log::warn!("Failed to load presets: {}", e);
+        Presets::default()
+    });
+    app.manage(Mutex::new(presets));
 
     let ctrl_d = Shortcut::new(Some(Modifiers::CONTROL), Code::KeyD);
     let shortcut_plugin = tauri_plugin_global_shortcut::Builder::new()
         .with_handler(move |_, shortcut, event| {
             if shortcut == &ctrl_d && event.state() == ShortcutState::Pressed {
-                if let Err(e) = hotkey::handle_shortcut(&app_handle, &item_text_to_trade_filters) {
+                let mappings = app_handle.state::<RwLock<Option<Mappings>>>();
+                let item_text_to_trade_filters = |text: &str| {
+                    let mappings = mappings
+                        .read()
+                        .map_err(|e| format!("Failed to read mappings: {}", e))?;
+                    let Some(mappings) = mappings.as_ref() else {
+                        // Checked once the trade data has loaded, a later copy replaces an earlier one.
+                        // Set while holding the read lock so storing the mappings can't miss it.
+                        *app_handle
+                            .state::<PendingItem>()
+                            .0
+                            .lock()
+                            .map_err(|e| format!("Failed to queue item: {}", e))? = Some(text.to_string());
+                        return Ok(None);
+                    };
+                    let filters = mappings.item_text_to_trade_filters(text)?;
+                    // A newly copied item replaces the one being checked, drop its searches
+                    app_handle.state::<ActiveCheck>().restart();
+                    Ok(Some(filters))
+                };
+                if let Err(e) = hotkey::handle_shortcut(&app_handle, item_text_to_trade_filters) {
                     show_error(&app_handle, e);
                 }
             }
@@ -90,19 +250,175 @@ pub fn run() {
             }
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![search_trade, minimize_window, open_trade_website, fetch_leagues])
+        .invoke_handler(tauri::generate_handler![search_trade, minimize_window, open_trade_website, fetch_leagues, refresh_mappings, import_trade_search, block_seller, unblock_seller, list_blocked_sellers, save_preset, list_presets, load_preset, rename_preset, delete_preset, set_session_id, has_session_id, start_live_search, stop_live_search, list_live_searches, mappings_status])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
+/// Price summary of a search once its whole sample is fetched, sent to the frontend as `price_summary`
+#[derive(Debug, Serialize)]
+struct PriceSummaryUpdate {
+    query_id: String,
+    base_currency: Option<String>,
+    summary: Option<PriceSummary>,
+}
+
+// Rates for converting the currencies, left empty if the cache can't be read
+async fn listing_rates<'a>(
+    app_handle: &tauri::AppHandle,
+    client: &Arc<TradeClient>,
+    query: &TradeQuery,
+    currencies: impl IntoIterator<Item = &'a str>,
+) -> CurrencyRates {
+    let cache = app_handle.state::<CurrencyRateCache>();
+    match cache.rates(client, query.realm.as_deref(), &query.league, currencies).await {
+        Ok(rates) => rates,
+        Err(e) => {
+            log::warn!("Failed to read currency rates, showing prices as listed: {}", e);
+            CurrencyRates::default()
+        }
+    }
+}
+
+// Summary of the priced listings among the search's first results that are fetched so far
+async fn summarize(
+    app_handle: &tauri::AppHandle,
+    client: &Arc<TradeClient>,
+    search: &CachedSearch,
+    query: &TradeQuery,
+    blocklist: &Blocklist,
+    base_currency: Option<&str>,
+) -> Option<PriceSummary> {
+    let sample = trade_api::sample_listings(search, trade_api::SUMMARY_SAMPLE_SIZE, blocklist);
+    let mut prices = sample
+        .into_iter()
+        .filter_map(|listing| listing.listing.price)
+        .collect::<Vec<_>>();
+
+    if let Some(base_currency) = base_currency {
+        let currencies = prices.iter().map(|price| price.currency.as_str()).chain([base_currency]);
+        let rates = listing_rates(app_handle, client, query, currencies).await;
+        // Prices without a rate stay as listed and only count towards their own currency
+        for price in &mut prices {
+            if let Some(converted) = rates.convert(price, base_currency) {
+                *price = converted;
+            }
+        }
+    }
+    PriceSummary::from_prices(&prices)
+}
+
+fn exchange_query(app_handle: &tauri::AppHandle, filters: &TradeFilters) -> Result<Option<ExchangeQuery>, String> {
+    with_mappings(app_handle, |mappings| mappings.exchange_query(filters))
+}
+
 #[tauri::command]
-async fn search_trade(filters: String, page: u32) -> Result<String, String> {
+async fn search_trade(
+    filters: String,
+    page: u32,
+    base_currency: Option<String>,
+    app_handle: tauri::AppHandle,
+    client: tauri::State<'_, Arc<TradeClient>>,
+    cache: tauri::State<'_, SearchCache>,
+    active_check: tauri::State<'_, ActiveCheck>,
+) -> Result<String, TradeError> {
+    // Only the latest search matters, one still running for older filters is dropped.
+    // Paging through the same search keeps its token so the prefetched page isn't thrown away.
+    let token = active_check.search(&filters);
+
     let filters: TradeFilters =
         serde_json::from_str(&filters).map_err(|e| format!("Failed to parse filters: {}", e))?;
 
+    // Currency and other bulk items are priced on the exchange, which has no pages
+    if let Some(query) = exchange_query(&app_handle, &filters)? {
+        let result = PriceCheckResult::Exchange(cancellable(&token, trade_api::exchange(&client, &query)).await?);
+        return serde_json::to_string(&result)
+            .map_err(|e| format!("Failed to serialize exchange result: {}", e).into());
+    }
+
     let query = TradeQuery::from_trade_filters(&filters);
-    let client = create_client().map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-    trade_api::search_trade(&client, &query, page).await
+    // Search against a snapshot so the lock isn't held across requests
+    let blocklist = app_handle
+        .state::<Mutex<Blocklist>>()
+        .lock()
+        .map_err(|e| format!("Failed to read blocked sellers: {}", e))?
+        .clone();
+
+    let search = cancellable(&token, trade_api::search(&client, &cache, &query)).await?;
+
+    // Sorting by price in one currency reorders the whole summary sample rather than each page,
+    // so paging through it keeps one order. That needs the sample and its rates up front.
+    let ids = match base_currency.as_deref() {
+        Some(base_currency) if filters.sort.key == SortKey::Price => {
+            let sample_size = trade_api::SUMMARY_SAMPLE_SIZE;
+            cancellable(&token, trade_api::fetch_sample(&client, &search, sample_size)).await?;
+            let sample = trade_api::sample_listings(&search, sample_size, &blocklist);
+            let currencies = sample
+                .iter()
+                .filter_map(|listing| listing.listing.price.as_ref())
+                .map(|price| price.currency.as_str())
+                .chain([base_currency]);
+            let rates = cancellable(&token, async {
+                Ok(listing_rates(&app_handle, &client, &query, currencies).await)
+            })
+            .await?;
+            order_by_normalized_price(&search, sample_size, &rates, base_currency, filters.sort.direction)
+        }
+        _ => search.result_ids.clone(),
+    };
+
+    let page_listings = trade_api::fetch_page(&client, &search, &ids, page, &blocklist);
+    let mut trade_result = cancellable(&token, page_listings).await?;
+
+    if let Some(base_currency) = base_currency.as_deref() {
+        let currencies = trade_result
+            .result
+            .iter()
+            .filter_map(|listing| listing.listing.price.as_ref())
+            .map(|price| price.currency.as_str())
+            .chain([base_currency]);
+        let rates = cancellable(&token, async {
+            Ok(listing_rates(&app_handle, &client, &query, currencies).await)
+        })
+        .await?;
+        rates.normalize(&mut trade_result.result, base_currency);
+    }
+
+    // Summarizes the listings fetched so far, the full sample follows as a `price_summary` event
+    trade_result.summary = summarize(&app_handle, &client, &search, &query, &blocklist, base_currency.as_deref()).await;
+
+    // Fetch the next page and the rest of the summary's sample in the background, so paging
+    // forward is instant and the page isn't held back by the sample
+    let client = client.inner().clone();
+    let total_pages = trade_result.total_pages;
+    tauri::async_runtime::spawn(async move {
+        if (page as usize) < total_pages {
+            let prefetch = trade_api::fetch_page(&client, &search, &ids, page + 1, &blocklist);
+            if let Err(e) = cancellable(&token, prefetch).await {
+                log::info!("Failed to prefetch page {}: {}", page + 1, e);
+            }
+        }
+
+        let sample = trade_api::fetch_sample(&client, &search, trade_api::SUMMARY_SAMPLE_SIZE);
+        if let Err(e) = cancellable(&token, sample).await {
+            log::info!("Failed to sample listings for the price summary: {}", e);
+            return;
+        }
+        let update = PriceSummaryUpdate {
+            query_id: search.id.clone(),
+            summary: summarize(&app_handle, &client, &search, &query, &blocklist, base_currency.as_deref()).await,
+            base_currency,
+        };
+        if let Err(e) = app_handle.emit("price_summary", &update) {
+            log::warn!("Failed to emit price summary: {}", e);
+        }
+    });
+
+    let result_str = serde_json::to_string(&PriceCheckResult::Items(trade_result))
+        .map_err(|e| format!("Failed to serialize TradeResult: {}", e))?;
+
+    log::info!("Final result: {}", result_str);
+    Ok(result_str)
 }
 
 #[tauri::command]
@@ -113,19 +429,30 @@ async fn minimize_window(app_handle: tauri::AppHandle) {
 }
 
 #[tauri::command]
-async fn open_trade_website(filters: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+async fn open_trade_website(
+    filters: String,
+    app_handle: tauri::AppHandle,
+    client: tauri::State<'_, Arc<TradeClient>>,
+    cache: tauri::State<'_, SearchCache>,
+) -> Result<(), TradeError> {
     let filters: TradeFilters =
         serde_json::from_str(&filters).map_err(|e| format!("Failed to parse filters: {}", e))?;
 
-    let query = TradeQuery::from_trade_filters(&filters);
-
-    let client = create_client().map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-    let query_id = trade_api::get_query_id(&client, &query).await.map_err(|e| format!("Failed to get query ID: {}", e))?;
+    let url = match exchange_query(&app_handle, &filters)? {
+        Some(query) => {
+            let result = trade_api::exchange(&client, &query).await?;
+            client.endpoints.exchange_website_url(query.realm.as_deref(), &query.league, &result.query_id)
+        }
+        None => {
+            let query = TradeQuery::from_trade_filters(&filters);
 
-    log::info!("Query ID: {}", query_id);
+            // Unchanged filters reuse the id of the search we just ran, only edited ones are posted again
+            let search = trade_api::search(&client, &cache, &query).await?;
+            log::info!("Query ID: {}", search.id);
 
-    let encoded_league = urlencoding::encode(&query.league);
-    let url = format!("https://www.pathofexile.com/trade2/search/poe2/{}/{}", encoded_league, query_id);
+            client.endpoints.website_url(query.realm.as_deref(), &query.league, &search.id)
+        }
+    };
 
     app_handle
         .opener()
@@ -136,8 +463,196 @@ async fn open_trade_website(filters: String, app_handle: tauri::AppHandle) -> Re
 }
 
 #[tauri::command]
-async fn fetch_leagues() -> Result<String, String> {
-    let client = create_client().map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-    let leagues = trade_api::fetch_leagues(&client).await?;
-    serde_json::to_string(&leagues).map_err(|e| format!("Failed to serialize leagues: {}", e))
+async fn fetch_leagues(app_handle: tauri::AppHandle) -> Result<String, TradeError> {
+    let leagues = trade_api::fetch_leagues(&app_handle.state::<Arc<TradeClient>>()).await?;
+
+    let league_ids: Vec<String> = leagues.iter().map(|league| league.id.clone()).collect();
+    // Mappings that are still loading will be fetched fresh anyway
+    let is_league_change = with_mappings(&app_handle, |mappings| mappings.is_league_change(&league_ids)).unwrap_or(false);
+    if is_league_change {
+        log::info!("League change detected, refreshing trade mappings");
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = reload_mappings(&app_handle).await {
+                log::warn!("Failed to refresh trade mappings: {}", e);
+            }
+        });
+    }
+
+    serde_json::to_string(&leagues).map_err(|e| format!("Failed to serialize leagues: {}", e).into())
+}
+
+#[tauri::command]
+async fn refresh_mappings(app_handle: tauri::AppHandle) -> Result<bool, String> {
+    let is_loaded = with_mappings(&app_handle, |_| ()).is_ok();
+    if is_loaded {
+        return reload_mappings(&app_handle).await;
+    }
+
+    // Retrying after the startup load failed
+    set_mappings_status(&app_handle, MappingsStatus::Loading);
+    reload_mappings(&app_handle).await.inspect_err(|e| {
+        set_mappings_status(&app_handle, MappingsStatus::Failed { error: e.clone() });
+    })
+}
+
+#[tauri::command]
+fn mappings_status(status: tauri::State<'_, Mutex<MappingsStatus>>) -> Result<MappingsStatus, String> {
+    status
+        .lock()
+        .map(|status| status.clone())
+        .map_err(|e| format!("Failed to read mappings status: {}", e))
+}
+
+/// Filters of an imported search, with the parts of it they couldn't carry over
+#[derive(Serialize)]
+struct ImportedSearch {
+    filters: TradeFilters,
+    dropped: Vec<String>,
+}
+
+/// Turns a pasted trade site link or query JSON into filters. Raw JSON doesn't name a league,
+/// so it's searched in the given one.
+#[tauri::command]
+async fn import_trade_search(
+    input: String,
+    realm: Option<String>,
+    league: String,
+    app_handle: tauri::AppHandle,
+    client: tauri::State<'_, Arc<TradeClient>>,
+) -> Result<String, TradeError> {
+    let input = input.trim();
+    let query = match client.endpoints.parse_website_url(input) {
+        Some((realm, league, id)) => trade_api::fetch_search(&client, realm.as_deref(), &league, &id).await?,
+        None => {
+            let json = serde_json::from_str(input)
+                .map_err(|_| "Expected a trade site search link or query JSON".to_string())?;
+            TradeQuery::from_search_json(json, realm, league)?
+        }
+    };
+
+    let filters = with_mappings(&app_handle, |mappings| mappings.trade_query_to_trade_filters(&query))?;
+    let dropped = query.dropped_filters();
+    if !dropped.is_empty() {
+        log::warn!("Imported search drops unsupported filters: {}", dropped.join(", "));
+    }
+    serde_json::to_string(&ImportedSearch { filters, dropped })
+        .map_err(|e| format!("Failed to serialize filters: {}", e).into())
+}
+
+#[tauri::command]
+async fn block_seller(account: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<Mutex<Blocklist>>();
+    let mut blocklist = state.lock().map_err(|e| format!("Failed to update blocked sellers: {}", e))?;
+    if blocklist.block(&account) {
+        log::info!("Blocked seller {}", account);
+        blocklist.save()?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn unblock_seller(account: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<Mutex<Blocklist>>();
+    let mut blocklist = state.lock().map_err(|e| format!("Failed to update blocked sellers: {}", e))?;
+    if blocklist.unblock(&account) {
+        log::info!("Unblocked seller {}", account);
+        blocklist.save()?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_blocked_sellers(app_handle: tauri::AppHandle) -> Result<String, String> {
+    let accounts = app_handle
+        .state::<Mutex<Blocklist>>()
+        .lock()
+        .map_err(|e| format!("Failed to read blocked sellers: {}", e))?
+        .accounts();
+    serde_json::to_string(&accounts).map_err(|e| format!("Failed to serialize blocked sellers: {}", e))
+}
+
+#[tauri::command]
+async fn save_preset(name: String, filters: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let filters: TradeFilters =
+        serde_json::from_str(&filters).map_err(|e| format!("Failed to parse filters: {}", e))?;
+
+    let state = app_handle.state::<Mutex<Presets>>();
+    let mut presets = state.lock().map_err(|e| format!("Failed to update presets: {}", e))?;
+    presets.update(|presets| presets.insert(&name, filters))
+}
+
+#[tauri::command]
+async fn list_presets(app_handle: tauri::AppHandle) -> Result<String, String> {
+    let names = app_handle
+        .state::<Mutex<Presets>>()
+        .lock()
+        .map_err(|e| format!("Failed to read presets: {}", e))?
+        .names();
+    serde_json::to_string(&names).map_err(|e| format!("Failed to serialize presets: {}", e))
+}
+
+#[tauri::command]
+async fn load_preset(name: String, app_handle: tauri::AppHandle) -> Result<String, String> {
+    let state = app_handle.state::<Mutex<Presets>>();
+    let presets = state.lock().map_err(|e| format!("Failed to read presets: {}", e))?;
+    let filters = presets.get(&name).ok_or_else(|| format!("No preset named {}", name))?;
+    serde_json::to_string(filters).map_err(|e| format!("Failed to serialize filters: {}", e))
+}
+
+#[tauri::command]
+async fn rename_preset(name: String, new_name: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<Mutex<Presets>>();
+    let mut presets = state.lock().map_err(|e| format!("Failed to update presets: {}", e))?;
+    presets.update(|presets| presets.rename(&name, &new_name))
+}
+
+#[tauri::command]
+async fn delete_preset(name: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<Mutex<Presets>>();
+    let mut presets = state.lock().map_err(|e| format!("Failed to update presets: {}", e))?;
+    if presets.get(&name).is_some() {
+        presets.update(|presets| Ok(presets.remove(&name)))?;
+    }
+    Ok(())
+}
+
+/// Saves the POESESSID cookie used to search as a logged in user. An empty id logs out.
+#[tauri::command]
+async fn set_session_id(session_id: String, client: tauri::State<'_, Arc<TradeClient>>) -> Result<(), String> {
+    let session_id = Some(session_id.trim()).filter(|session_id| !session_id.is_empty());
+    trade_client::store_session_id(session_id)?;
+    client.set_session_id(session_id);
+    Ok(())
+}
+
+#[tauri::command]
+async fn has_session_id() -> Result<bool, String> {
+    Ok(trade_client::load_session_id()?.is_some())
+}
+
+/// Starts pushing new listings of the search to the frontend as `live_search` events
+#[tauri::command]
+async fn start_live_search(
+    realm: Option<String>,
+    league: String,
+    query_id: String,
+    app_handle: tauri::AppHandle,
+    client: tauri::State<'_, Arc<TradeClient>>,
+    live_searches: tauri::State<'_, LiveSearchManager>,
+) -> Result<(), TradeError> {
+    live_searches.start(client.inner().clone(), realm.as_deref(), &league, &query_id, move |event| {
+        if let Err(e) = app_handle.emit("live_search", &event) {
+            log::warn!("Failed to emit live search event: {}", e);
+        }
+    })
+}
+
+#[tauri::command]
+async fn stop_live_search(query_id: String, live_searches: tauri::State<'_, LiveSearchManager>) -> Result<bool, String> {
+    Ok(live_searches.stop(&query_id))
+}
+
+#[tauri::command]
+async fn list_live_searches(live_searches: tauri::State<'_, LiveSearchManager>) -> Result<Vec<String>, String> {
+    Ok(live_searches.active())
 }
@@ -29,7 +29,42 @@ struct ItemFlags {
 }
 
 pub struct BaseTypeMap {
-    mapping: HashMap<String, String>,
+    base_types: WordTrie,
+    /// Unique name to the base type it uses
+    uniques: HashMap<String, String>,
+}
+
+/// Base types keyed word by word, so every base starting at a given word is found in one walk
+#[derive(Default)]
+struct WordTrie {
+    children: HashMap<String, WordTrie>,
+    /// Category of the base type that ends at this word
+    category: Option<String>,
+}
+
+impl WordTrie {
+    fn insert(&mut self, base_type: &str, category: String) {
+        let node = base_type
+            .split_whitespace()
+            .fold(self, |node, word| node.children.entry(word.to_string()).or_default());
+        node.category = Some(category);
+    }
+
+    /// Returns the word count and category of the longest base type starting at `words[0]`
+    fn longest_match(&self, words: &[&str]) -> Option<(usize, &str)> {
+        let mut node = self;
+        let mut longest = None;
+        for (i, word) in words.iter().enumerate() {
+            match node.children.get(*word) {
+                Some(child) => node = child,
+                None => break,
+            }
+            if let Some(category) = &node.category {
+                longest = Some((i + 1, category.as_str()));
+            }
+        }
+        longest
+    }
 }
 
 impl BaseTypeMap {
@@ -37,32 +72,70 @@ impl BaseTypeMap {
         let mapping: ItemMapping = serde_json::from_value(json)
             .map_err(|e| format!("Failed to parse item mapping: {}", e))?;
 
-        let mut map = HashMap::new();
+        let mut base_types = WordTrie::default();
+        let mut uniques = HashMap::new();
         for category in mapping.result {
             for entry in category.entries {
-                // Only add non-unique items as base types
-                if entry.flags.is_none() || !entry.flags.unwrap().unique.unwrap_or(false) {
-                    map.insert(entry.base_type, category.id.clone());
+                let is_unique = entry.flags.and_then(|flags| flags.unique).unwrap_or(false);
+                if !is_unique {
+                    base_types.insert(&entry.base_type, category.id.clone());
+                } else if let Some(name) = entry.name {
+                    // Uniques are indexed separately by name so they never match as base types
+                    uniques.insert(name, entry.base_type);
                 }
             }
         }
 
-        Ok(Self { mapping: map })
+        Ok(Self {
+            base_types,
+            uniques,
+        })
     }
 
+    /// Looks up the base type of a unique by name
+    pub fn unique_to_base_type(&self, name: &str) -> Option<String> {
+        self.uniques.get(name).cloned()
+    }
+
+    /// Lists the names of all uniques that use the given base type, sorted by name
+    pub fn base_type_to_uniques(&self, base_type: &str) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .uniques
+            .iter()
+            .filter(|(_, unique_base_type)| *unique_base_type == base_type)
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Finds the longest base type in the name lines of the item text (the header before the first
+    /// separator, minus the item class and rarity), so words in mods can never match
     pub fn item_text_to_base_type(&self, item_text: &str) -> Option<(String, String)> {
-        let words: Vec<&str> = item_text.split_whitespace().collect();
-
-        // Try all possible contiguous combinations, starting with longer ones
-        for start in 0..words.len() {
-            for len in (1..=words.len() - start).rev() {
-                let combination = words[start..start + len].join(" ");
-                if let Some(category) = self.mapping.get(&combination) {
-                    return Some((combination.clone(), category.clone()));
+        let header = item_text.split("--------").next().unwrap_or_default();
+        let name_lines = header
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.starts_with("Item Class:") && !line.starts_with("Rarity:"));
+
+        let mut longest: Option<(usize, String, &str)> = None;
+        for line in name_lines {
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            for start in 0..words.len() {
+                if let Some((len, category)) = self.base_types.longest_match(&words[start..]) {
+                    let is_longer = match &longest {
+                        Some((longest_len, _, _)) => len > *longest_len,
+                        None => true,
+                    };
+                    if is_longer {
+                        longest = Some((len, words[start..start + len].join(" "), category));
+                    }
                 }
             }
         }
-        None
+
+        longest.map(|(_, base_type, category)| (base_type, category.to_string()))
     }
 }
 
@@ -108,4 +181,170 @@ mod tests {
         assert_eq!(base_type, "Advanced Dualstring Bow");
         assert_eq!(category, "weapon");
     }
+
+    #[test]
+    fn test_unique_index() {
+        let json = serde_json::json!({
+            "result": [{
+                "id": "weapon",
+                "label": "Weapons",
+                "entries": [
+                    {
+                        "type": "Crude Bow",
+                        "text": "Crude Bow"
+                    },
+                    {
+                        "name": "Widowhail",
+                        "type": "Crude Bow",
+                        "text": "Widowhail Crude Bow",
+                        "flags": { "unique": true }
+                    },
+                    {
+                        "name": "Quill Rain",
+                        "type": "Crude Bow",
+                        "text": "Quill Rain Crude Bow",
+                        "flags": { "unique": true }
+                    }
+                ]
+            },
+            {
+                "id": "accessory",
+                "label": "Accessories",
+                "entries": [
+                    {
+                        "name": "Andvarius",
+                        "type": "Gold Ring",
+                        "text": "Andvarius Gold Ring",
+                        "flags": { "unique": true }
+                    }
+                ]
+            }]
+        });
+
+        let base_type_map = BaseTypeMap::new(json).unwrap();
+
+        assert_eq!(
+            base_type_map.unique_to_base_type("Andvarius"),
+            Some("Gold Ring".to_string())
+        );
+        assert_eq!(base_type_map.unique_to_base_type("Crude Bow"), None);
+
+        assert_eq!(
+            base_type_map.base_type_to_uniques("Crude Bow"),
+            vec!["Quill Rain".to_string(), "Widowhail".to_string()]
+        );
+        assert!(base_type_map.base_type_to_uniques("Gold Amulet").is_empty());
+
+        // Unique names must not be picked up as base types
+        assert_eq!(base_type_map.item_text_to_base_type("Widowhail"), None);
+    }
+
+    #[test]
+    fn test_longest_base_type_in_name_lines() {
+        let json = serde_json::json!({
+            "result": [{
+                "id": "weapon",
+                "label": "Weapons",
+                "entries": [
+                    { "type": "Dualstring Bow", "text": "Dualstring Bow" },
+                    { "type": "Advanced Dualstring Bow", "text": "Advanced Dualstring Bow" },
+                    { "type": "Crude Bow", "text": "Crude Bow" }
+                ]
+            },
+            {
+                "id": "accessory",
+                "label": "Accessories",
+                "entries": [
+                    { "type": "Ring", "text": "Ring" },
+                    { "type": "Gold Ring", "text": "Gold Ring" }
+                ]
+            }]
+        });
+
+        let base_type_map = BaseTypeMap::new(json).unwrap();
+
+        // The longest base wins even when a shorter one starts further left
+        const MAGIC_BOW: &str = r#"Item Class: Bows
+Rarity: Magic
+Advanced Dualstring Bow of the Ring
+--------
+Crude Bow attacks are ignored here"#;
+        let (base_type, category) = base_type_map.item_text_to_base_type(MAGIC_BOW).unwrap();
+        assert_eq!(base_type, "Advanced Dualstring Bow");
+        assert_eq!(category, "weapon");
+
+        const MAGIC_RING: &str = r#"Item Class: Rings
+Rarity: Magic
+Gold Ring of the Whale
+--------
+Item Level: 60"#;
+        let (base_type, category) = base_type_map.item_text_to_base_type(MAGIC_RING).unwrap();
+        assert_eq!(base_type, "Gold Ring");
+        assert_eq!(category, "accessory");
+
+        // Words in mods below the header are never matched
+        const NO_BASE: &str = r#"Item Class: Rings
+Rarity: Magic
+Unknown Band
+--------
+Crude Bow"#;
+        assert_eq!(base_type_map.item_text_to_base_type(NO_BASE), None);
+    }
+
+    #[test]
+    fn test_word_trie_longest_match() {
+        let mut trie = WordTrie::default();
+        trie.insert("Dualstring Bow", "weapon".to_string());
+        trie.insert("Advanced Dualstring Bow", "weapon".to_string());
+        trie.insert("Gold", "currency".to_string());
+        trie.insert("Gold Ring", "accessory".to_string());
+
+        assert_eq!(trie.longest_match(&["Gold", "Ring", "of", "the", "Whale"]), Some((2, "accessory")));
+        // A shorter base is still found when the longer one doesn't continue
+        assert_eq!(trie.longest_match(&["Gold", "Amulet"]), Some((1, "currency")));
+        assert_eq!(trie.longest_match(&["Advanced", "Dualstring", "Bow"]), Some((3, "weapon")));
+        // Only bases starting at the first word count, inner nodes aren't bases
+        assert_eq!(trie.longest_match(&["Advanced", "Dualstring"]), None);
+        assert_eq!(trie.longest_match(&["Horror", "Gold", "Ring"]), None);
+        assert_eq!(trie.longest_match(&[]), None);
+    }
+
+    #[test]
+    fn test_base_type_among_many_similar_bases() {
+        // Many bases sharing words, so a lookup has to follow the right branch of the trie
+        let entries: Vec<_> = (0..2000)
+            .map(|i| serde_json::json!({ "type": format!("Base{} Type{} Item", i, i % 50), "text": "" }))
+            .chain([
+                serde_json::json!({ "type": "Dualstring Bow", "text": "" }),
+                serde_json::json!({ "type": "Advanced Dualstring Bow", "text": "" }),
+            ])
+            .collect();
+        let json = serde_json::json!({
+            "result": [{ "id": "weapon", "label": "Weapons", "entries": entries }]
+        });
+        let base_type_map = BaseTypeMap::new(json).unwrap();
+
+        let item_text = r#"Item Class: Bows
+Rarity: Rare
+Horror Thunder
+Advanced Dualstring Bow
+--------
+Physical Damage: 64-118 (augmented)
+Attacks per Second: 1.36 (augmented)
+--------
+Item Level: 58
+--------
+Base7 Type7 Item
+Bow Attacks fire an additional Arrow"#;
+        let (base_type, category) = base_type_map.item_text_to_base_type(item_text).unwrap();
+        assert_eq!(base_type, "Advanced Dualstring Bow");
+        assert_eq!(category, "weapon");
+
+        let (base_type, _) = base_type_map
+            .item_text_to_base_type("Rarity: Magic\nShiny Base1234 Type34 Item of Haste")
+            .unwrap();
+        assert_eq!(base_type, "Base1234 Type34 Item");
+        // Right words in the wrong combination are no base
+        assert_eq!(base_type_map.item_text_to_base_type("Rarity: Magic\nBase1234 Type35 Item"), None);
+    }
 }
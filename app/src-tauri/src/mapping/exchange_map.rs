@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Static data categories traded in bulk on the exchange rather than as single items.
+// Omens are listed under Ritual and soul cores under Ultimatum.
+const EXCHANGE_CATEGORIES: &[&str] = &[
+    "Currency",
+    "Fragments",
+    "Runes",
+    "Ritual",
+    "Ultimatum",
+    "Essences",
+    "Expedition",
+    "Breach",
+    "Delirium",
+    "Abyss",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StaticMapping {
+    result: Vec<StaticCategory>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StaticCategory {
+    id: String,
+    entries: Vec<StaticEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StaticEntry {
+    id: String,
+    // Separators between groups of entries have no text
+    text: Option<String>,
+}
+
+/// Exchange ids of bulk tradeable items keyed by their in-game name, from `/data/static`
+#[derive(Debug, Default)]
+pub struct ExchangeMap {
+    ids: HashMap<String, String>,
+}
+
+impl ExchangeMap {
+    pub fn new(json: Value) -> Result<Self, String> {
+        // Caches from before static data was fetched have none, so nothing is routed to the exchange
+        if json.is_null() {
+            return Ok(Self::default());
+        }
+
+        let mapping: StaticMapping = serde_json::from_value(json)
+            .map_err(|e| format!("Failed to parse static mapping: {}", e))?;
+
+        let ids = mapping
+            .result
+            .into_iter()
+            .filter(|category| EXCHANGE_CATEGORIES.contains(&category.id.as_str()))
+            .flat_map(|category| category.entries)
+            .filter_map(|entry| Some((entry.text?, entry.id)))
+            .collect();
+
+        Ok(Self { ids })
+    }
+
+    /// Exchange id of the item, if it's traded in bulk
+    pub fn exchange_id(&self, item_name: &str) -> Option<&str> {
+        self.ids.get(item_name).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exchange_ids_by_name() {
+        let json = serde_json::json!({
+            "result": [
+                {
+                    "id": "Currency",
+                    "label": "Currency",
+                    "entries": [
+                        { "id": "exalted", "text": "Exalted Orb", "image": "/exalted.png" },
+                        { "id": "sep" }
+                    ]
+                },
+                {
+                    "id": "Ritual",
+                    "label": "Omens",
+                    "entries": [{ "id": "omen-of-light", "text": "Omen of Light" }]
+                },
+                {
+                    "id": "Waystones",
+                    "label": "Waystones",
+                    "entries": [{ "id": "waystone-15", "text": "Waystone (Tier 15)" }]
+                }
+            ]
+        });
+
+        let map = ExchangeMap::new(json).expect("Should parse static data");
+        assert_eq!(map.exchange_id("Exalted Orb"), Some("exalted"));
+        assert_eq!(map.exchange_id("Omen of Light"), Some("omen-of-light"));
+        assert_eq!(map.exchange_id("Waystone (Tier 15)"), None);
+        assert_eq!(map.exchange_id("Advanced Dualstring Bow"), None);
+    }
+}
@@ -1,2 +1,91 @@
 pub mod mod_pattern_map;
 pub mod base_type_map;
+pub mod exchange_map;
+
+use crate::error::TradeError;
+use crate::model::exchange::ExchangeQuery;
+use crate::model::trade_filter::TradeFilters;
+use crate::model::trade_query::TradeQuery;
+use crate::service::mapping_cache::CachedMappings;
+use base_type_map::BaseTypeMap;
+use exchange_map::ExchangeMap;
+use mod_pattern_map::ModPatternMap;
+
+// Currencies exchange offers are priced in
+const PRICE_CURRENCIES: &[&str] = &["exalted", "divine", "chaos"];
+
+pub struct Mappings {
+    pub mod_pattern_map: ModPatternMap,
+    pub base_type_map: BaseTypeMap,
+    pub exchange_map: ExchangeMap,
+    /// Hash of the trade data the maps were built from
+    pub hash: String,
+    /// League ids that were live when the trade data was fetched
+    pub leagues: Vec<String>,
+}
+
+impl Mappings {
+    pub fn from_cache(cached: CachedMappings) -> Result<Self, String> {
+        let mod_pattern_map = ModPatternMap::new(cached.stats)
+            .map_err(|e| format!("Failed to create mod pattern map: {}", e))?;
+        let base_type_map = BaseTypeMap::new(cached.items)
+            .map_err(|e| format!("Failed to create base type map: {}", e))?;
+        let exchange_map = ExchangeMap::new(cached.static_data)
+            .map_err(|e| format!("Failed to create exchange map: {}", e))?;
+
+        Ok(Self {
+            mod_pattern_map,
+            base_type_map,
+            exchange_map,
+            hash: cached.hash,
+            leagues: cached.leagues,
+        })
+    }
+
+    pub fn item_text_to_trade_filters(&self, text: &str) -> Result<TradeFilters, TradeError> {
+        TradeFilters::from_text(
+            |text, prefix| self.mod_pattern_map.mod_pattern_to_trade_stat(text, prefix),
+            |text| self.base_type_map.item_text_to_base_type(text),
+            |name| self.base_type_map.unique_to_base_type(name),
+            |base_type| self.base_type_map.base_type_to_uniques(base_type),
+            text,
+        )
+    }
+
+    pub fn trade_query_to_trade_filters(&self, query: &TradeQuery) -> TradeFilters {
+        query.to_trade_filters(|trade_stat| self.mod_pattern_map.trade_stat_to_text(trade_stat))
+    }
+
+    /// Bulk exchange search for items like currency, omens and runes, None for items
+    /// that are searched one by one
+    pub fn exchange_query(&self, filters: &TradeFilters) -> Option<ExchangeQuery> {
+        if filters.item_name.as_ref().is_some_and(|name| name.enabled) {
+            return None;
+        }
+        let base_type = filters.item_base_type.as_ref().filter(|base_type| base_type.enabled)?;
+        let want = self.exchange_map.exchange_id(&base_type.text)?;
+
+        let have = PRICE_CURRENCIES
+            .iter()
+            .filter(|currency| **currency != want)
+            .map(|currency| currency.to_string())
+            .collect();
+        let league = filters
+            .league
+            .as_ref()
+            .map(|league| league.text.clone())
+            .unwrap_or_else(|| "Standard".to_string());
+        let realm = filters.realm.as_ref().map(|realm| realm.text.clone());
+        Some(ExchangeQuery::new(realm, league, filters.status.option, have, vec![want.to_string()]))
+    }
+
+    /// Whether the live leagues differ from the ones seen when the trade data was fetched,
+    /// which usually means a new league (and game patch) has launched
+    pub fn is_league_change(&self, league_ids: &[String]) -> bool {
+        let mut known = self.leagues.clone();
+        let mut current = league_ids.to_vec();
+        known.sort();
+        current.sort();
+        known != current
+    }
+}
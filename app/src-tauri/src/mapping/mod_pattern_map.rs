@@ -22,6 +22,8 @@ struct ModPatternEntry {
 
 pub struct ModPatternMap {
     mapping: HashMap<String, Vec<String>>,
+    // Reverse of the above, trade stat id to its pattern
+    texts: HashMap<String, String>,
 }
 
 impl ModPatternMap {
@@ -30,9 +32,11 @@ impl ModPatternMap {
             .map_err(|e| format!("Failed to parse stats mapping: {}", e))?;
 
         let mut pattern_map = HashMap::new();
+        let mut texts = HashMap::new();
         for section in mapping.result {
             for entry in section.entries {
                 let pattern = normalize_pattern(&entry.text);
+                texts.insert(entry.id.clone(), pattern.clone());
                 pattern_map
                     .entry(pattern)
                     .or_insert_with(Vec::new)
@@ -42,6 +46,7 @@ impl ModPatternMap {
 
         Ok(Self {
             mapping: pattern_map,
+            texts,
         })
     }
 
@@ -55,6 +60,10 @@ impl ModPatternMap {
         }
         None
     }
+
+    pub fn trade_stat_to_text(&self, trade_stat: &str) -> Option<String> {
+        self.texts.get(trade_stat).cloned()
+    }
 }
 
 fn normalize_pattern(text: &str) -> String {
@@ -143,5 +152,12 @@ mod tests {
             pattern_map.mod_pattern_to_trade_stat("#% increased Attack Speed", "implicit"),
             None
         );
+
+        // Test reverse lookup from trade stat to pattern
+        assert_eq!(
+            pattern_map.trade_stat_to_text("rune.stat_1509134228"),
+            Some("#% increased Physical Damage".to_string())
+        );
+        assert_eq!(pattern_map.trade_stat_to_text("explicit.stat_0"), None);
     }
 }
@@ -0,0 +1,344 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use super::trade_filter::{ListingStatus, SortDirection};
+use super::trade_query::OptionValue;
+use super::trade_result::AccountInfo;
+
+/// Body of a bulk exchange request, offers of `want` currency sold for `have` currency
+#[derive(Debug, Serialize)]
+pub struct ExchangeQuery {
+    pub query: ExchangeQueryBody,
+    pub sort: BTreeMap<String, SortDirection>,
+    pub engine: String,
+    #[serde(skip)]
+    pub league: String,
+    #[serde(skip)]
+    pub realm: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExchangeQueryBody {
+    pub status: OptionValue<ExchangeStatus>,
+    pub have: Vec<String>,
+    pub want: Vec<String>,
+}
+
+/// Listing statuses the exchange takes, it has no instant buyout and no per-league presence
+#[derive(Debug, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ExchangeStatus {
+    Online,
+    Any,
+}
+
+impl From<ListingStatus> for ExchangeStatus {
+    // The item search statuses all ask for sellers that are around, the closest is `online`
+    fn from(status: ListingStatus) -> Self {
+        match status {
+            ListingStatus::Any => Self::Any,
+            ListingStatus::Available
+            | ListingStatus::Securable
+            | ListingStatus::OnlineLeague
+            | ListingStatus::Online => Self::Online,
+        }
+    }
+}
+
+impl ExchangeQuery {
+    pub fn new(
+        realm: Option<String>,
+        league: String,
+        status: ListingStatus,
+        have: Vec<String>,
+        want: Vec<String>,
+    ) -> Self {
+        Self {
+            query: ExchangeQueryBody {
+                status: OptionValue { option: status.into() },
+                have,
+                want,
+            },
+            // Cheapest offers first
+            sort: BTreeMap::from([("have".to_string(), SortDirection::Asc)]),
+            engine: "new".to_string(),
+            league,
+            realm,
+        }
+    }
+}
+
+/// A page of exchange offers, flattened from the trade site's response
+#[derive(Debug, Clone, Serialize)]
+pub struct ExchangeResult {
+    pub offers: Vec<ExchangeOffer>,
+    pub total: usize,
+    pub query_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExchangeOffer {
+    pub id: String,
+    pub account: AccountInfo,
+    pub indexed: DateTime<Utc>,
+    /// What the seller asks for, in the currency we have
+    pub pay: ExchangeAmount,
+    /// What the seller gives, in the currency we want
+    pub get: ExchangeAmount,
+    /// How much of the wanted currency the seller has at this ratio
+    pub stock: u64,
+    /// Price of one wanted currency in the currency we have
+    pub ratio: f64,
+    pub whisper: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExchangeAmount {
+    pub currency: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawExchangeResponse {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    result: HashMap<String, RawExchangeListing>,
+    #[serde(default)]
+    total: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawExchangeListing {
+    id: String,
+    listing: RawListing,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawListing {
+    indexed: DateTime<Utc>,
+    account: AccountInfo,
+    #[serde(default)]
+    offers: Vec<RawOffer>,
+    whisper: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawOffer {
+    exchange: RawOfferSide,
+    item: RawOfferSide,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawOfferSide {
+    currency: String,
+    amount: f64,
+    #[serde(default)]
+    stock: u64,
+    whisper: Option<String>,
+}
+
+impl RawOfferSide {
+    // Side whispers are templates like "{0} Exalted Orb", with the amount to fill in
+    fn whisper(&self) -> Option<String> {
+        self.whisper
+            .as_ref()
+            .map(|whisper| whisper.replace("{0}", &self.amount.to_string()))
+    }
+}
+
+impl ExchangeResult {
+    /// Parses an exchange response, one offer per listed ratio. Ratios in different currencies
+    /// can't be compared, so offers are grouped by the currency they ask for, in the order of
+    /// `have`, and sorted cheapest first within each group.
+    pub fn from_response(json: serde_json::Value, have: &[String]) -> Result<Self, String> {
+        let response: RawExchangeResponse = serde_json::from_value(json)
+            .map_err(|e| format!("Failed to parse exchange response: {}", e))?;
+
+        let mut offers = Vec::new();
+        for listing in response.result.into_values() {
+            for offer in &listing.listing.offers {
+                if offer.item.amount <= 0.0 {
+                    continue;
+                }
+
+                // The listing whisper takes the wanted currency as {0} and the payment as {1}
+                let whisper = listing.listing.whisper.as_ref().map(|whisper| {
+                    whisper
+                        .replace("{0}", &offer.item.whisper().unwrap_or_default())
+                        .replace("{1}", &offer.exchange.whisper().unwrap_or_default())
+                });
+
+                offers.push(ExchangeOffer {
+                    id: listing.id.clone(),
+                    account: listing.listing.account.clone(),
+                    indexed: listing.listing.indexed,
+                    pay: ExchangeAmount {
+                        currency: offer.exchange.currency.clone(),
+                        amount: offer.exchange.amount,
+                    },
+                    get: ExchangeAmount {
+                        currency: offer.item.currency.clone(),
+                        amount: offer.item.amount,
+                    },
+                    stock: offer.item.stock,
+                    ratio: offer.exchange.amount / offer.item.amount,
+                    whisper,
+                });
+            }
+        }
+        let pay_rank = |offer: &ExchangeOffer| {
+            have
+                .iter()
+                .position(|currency| *currency == offer.pay.currency)
+                .unwrap_or(have.len())
+        };
+        offers.sort_by(|a, b| {
+            pay_rank(a)
+                .cmp(&pay_rank(b))
+                .then_with(|| a.pay.currency.cmp(&b.pay.currency))
+                .then_with(|| a.ratio.total_cmp(&b.ratio))
+        });
+
+        Ok(Self {
+            offers,
+            total: response.total,
+            query_id: response.id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_exchange_query_body() {
+        let query = ExchangeQuery::new(
+            None,
+            "Standard".to_string(),
+            ListingStatus::Online,
+            vec!["exalted".to_string()],
+            vec!["chaos".to_string()],
+        );
+        assert_eq!(
+            serde_json::to_value(&query).unwrap(),
+            json!({
+                "query": {
+                    "status": {"option": "online"},
+                    "have": ["exalted"],
+                    "want": ["chaos"]
+                },
+                "sort": {"have": "asc"},
+                "engine": "new"
+            })
+        );
+    }
+
+    #[test]
+    fn test_exchange_status_from_item_search_status() {
+        let status = |status| {
+            let query = ExchangeQuery::new(None, "Standard".to_string(), status, vec![], vec![]);
+            serde_json::to_value(&query).unwrap()["query"]["status"]["option"].clone()
+        };
+        assert_eq!(status(ListingStatus::Securable), "online");
+        assert_eq!(status(ListingStatus::Available), "online");
+        assert_eq!(status(ListingStatus::OnlineLeague), "online");
+        assert_eq!(status(ListingStatus::Any), "any");
+    }
+
+    #[test]
+    fn test_exchange_result_from_response() {
+        let response = json!({
+            "id": "AbC123",
+            "result": {
+                "first": {
+                    "id": "first",
+                    "item": null,
+                    "listing": {
+                        "indexed": "2025-02-07T02:26:42Z",
+                        "account": {"name": "Seller#1234", "online": {"league": "Standard"}},
+                        "offers": [{
+                            "exchange": {"currency": "exalted", "amount": 30, "whisper": "{0} Exalted Orb"},
+                            "item": {"currency": "chaos", "amount": 2, "stock": 40, "id": "x", "whisper": "{0} Chaos Orb"}
+                        }],
+                        "whisper": "@Seller Hi, I'd like to buy your {0} for my {1} in Standard."
+                    }
+                },
+                "second": {
+                    "id": "second",
+                    "item": null,
+                    "listing": {
+                        "indexed": "2025-02-07T02:26:42Z",
+                        "account": {"name": "Other#5678"},
+                        "offers": [{
+                            "exchange": {"currency": "exalted", "amount": 14},
+                            "item": {"currency": "chaos", "amount": 1, "stock": 3}
+                        }]
+                    }
+                }
+            },
+            "total": 2
+        });
+
+        let result = ExchangeResult::from_response(response, &["exalted".to_string()])
+            .expect("Should parse exchange response");
+        assert_eq!(result.query_id, "AbC123");
+        assert_eq!(result.total, 2);
+        assert_eq!(result.offers.len(), 2);
+
+        let cheapest = &result.offers[0];
+        assert_eq!(cheapest.account.name, "Other#5678");
+        assert_eq!(cheapest.ratio, 14.0);
+        assert_eq!(cheapest.stock, 3);
+
+        let second = &result.offers[1];
+        assert_eq!(second.ratio, 15.0);
+        assert_eq!(
+            second.whisper.as_deref(),
+            Some("@Seller Hi, I'd like to buy your 2 Chaos Orb for my 30 Exalted Orb in Standard.")
+        );
+    }
+
+    #[test]
+    fn test_exchange_offers_grouped_by_pay_currency() {
+        let offer = |id: &str, currency: &str, amount: u64| {
+            json!({
+                "id": id,
+                "listing": {
+                    "indexed": "2025-02-07T02:26:42Z",
+                    "account": {"name": id},
+                    "offers": [{
+                        "exchange": {"currency": currency, "amount": amount},
+                        "item": {"currency": "annul", "amount": 1, "stock": 5}
+                    }]
+                }
+            })
+        };
+        let response = json!({
+            "id": "AbC123",
+            "result": {
+                "a": offer("a", "divine", 1),
+                "b": offer("b", "exalted", 14),
+                "c": offer("c", "exalted", 12),
+                "d": offer("d", "chaos", 2)
+            },
+            "total": 4
+        });
+
+        let have = ["exalted".to_string(), "divine".to_string()];
+        let result = ExchangeResult::from_response(response, &have).unwrap();
+        let order = result
+            .offers
+            .iter()
+            .map(|offer| (offer.pay.currency.as_str(), offer.ratio))
+            .collect::<Vec<_>>();
+        // 1 divine is worth far more than 12 exalted, so the groups aren't interleaved by ratio
+        assert_eq!(
+            order,
+            vec![("exalted", 12.0), ("exalted", 14.0), ("divine", 1.0), ("chaos", 2.0)]
+        );
+    }
+}
@@ -1,3 +1,5 @@
+pub mod exchange;
+pub mod price_summary;
 pub mod trade_filter;
 pub mod trade_query;
 pub mod trade_result;
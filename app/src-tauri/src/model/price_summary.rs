@@ -0,0 +1,125 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::trade_result::PriceInfo;
+
+/// What a search's listings ask for, summarized into one ballpark price
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PriceSummary {
+    /// Currency the statistics are in, the one most listings are priced in
+    pub currency: String,
+    pub min: f64,
+    pub lower_quartile: f64,
+    pub median: f64,
+    pub upper_quartile: f64,
+    /// Listings priced in `currency` that the statistics were computed from
+    pub sampled: usize,
+    /// How many listings ask for each currency, most common first
+    pub currencies: Vec<CurrencyCount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CurrencyCount {
+    pub currency: String,
+    pub count: usize,
+}
+
+impl PriceSummary {
+    /// Summarizes the prices, None if there are none. Amounts in different currencies can't be
+    /// compared, so the statistics only cover the most common currency.
+    pub fn from_prices<'a>(prices: impl IntoIterator<Item = &'a PriceInfo>) -> Option<Self> {
+        let mut amounts: HashMap<&str, Vec<f64>> = HashMap::new();
+        for price in prices {
+            amounts.entry(price.currency.as_str()).or_default().push(price.amount);
+        }
+
+        let mut currencies = amounts
+            .iter()
+            .map(|(currency, amounts)| CurrencyCount {
+                currency: currency.to_string(),
+                count: amounts.len(),
+            })
+            .collect::<Vec<_>>();
+        currencies.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.currency.cmp(&b.currency)));
+
+        let currency = currencies.first()?.currency.clone();
+        let mut sorted = amounts.remove(currency.as_str())?;
+        sorted.sort_by(f64::total_cmp);
+
+        Some(Self {
+            min: sorted[0],
+            lower_quartile: percentile(&sorted, 0.25),
+            median: percentile(&sorted, 0.5),
+            upper_quartile: percentile(&sorted, 0.75),
+            sampled: sorted.len(),
+            currency,
+            currencies,
+        })
+    }
+}
+
+// Interpolates between the closest ranks of the sorted, non-empty amounts
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(amount: f64, currency: &str) -> PriceInfo {
+        PriceInfo {
+            price_type: "~price".to_string(),
+            amount,
+            currency: currency.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_summary_of_most_common_currency() {
+        let prices = vec![
+            price(5.0, "exalted"),
+            price(1.0, "divine"),
+            price(1.0, "exalted"),
+            price(3.0, "exalted"),
+            price(2.0, "exalted"),
+            price(4.0, "exalted"),
+        ];
+        let summary = PriceSummary::from_prices(&prices).unwrap();
+
+        assert_eq!(summary.currency, "exalted");
+        assert_eq!(summary.sampled, 5);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.lower_quartile, 2.0);
+        assert_eq!(summary.median, 3.0);
+        assert_eq!(summary.upper_quartile, 4.0);
+        assert_eq!(
+            summary.currencies,
+            vec![
+                CurrencyCount {
+                    currency: "exalted".to_string(),
+                    count: 5
+                },
+                CurrencyCount {
+                    currency: "divine".to_string(),
+                    count: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_summary_interpolates_between_listings() {
+        let prices = vec![price(1.0, "chaos"), price(2.0, "chaos")];
+        let summary = PriceSummary::from_prices(&prices).unwrap();
+        assert_eq!(summary.median, 1.5);
+        assert_eq!(summary.lower_quartile, 1.25);
+
+        assert_eq!(PriceSummary::from_prices(&[]), None);
+    }
+}
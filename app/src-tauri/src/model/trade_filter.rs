@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::error::TradeError;
+
+// Fields missing from older saved filters fall back to `TradeFilters::new()`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct TradeFilters {
     // Base Item Properties
     pub item_category: Option<TextFilter>,
@@ -15,6 +19,7 @@ pub struct TradeFilters {
     pub total_dps: Option<RangeFilter>,
     pub attack_speed: Option<RangeFilter>,
     pub critical_chance: Option<RangeFilter>,
+    pub reload_time: Option<RangeFilter>,
     pub socket_count: Option<RangeFilter>,
     pub armour: Option<RangeFilter>,
     pub energy_shield: Option<RangeFilter>,
@@ -29,19 +34,30 @@ pub struct TradeFilters {
     pub explicit_mods: Vec<StatFilter>,
     pub implicit_mods: Vec<StatFilter>,
     pub rune_mods: Vec<StatFilter>,
+    // Extra stat groups on top of the mods above, which always form an "and" group
+    pub stat_groups: Vec<StatGroup>,
 
     pub price: PriceFilter,
-    pub online_only: ToggleFilter,
+    pub status: OptionFilter<ListingStatus>,
+    pub sale_type: OptionFilter<SaleType>,
+    pub listed_within: OptionFilter<ListedWithin>,
+    pub account: Option<TextFilter>,
     pub league: Option<TextFilter>,
+    // Realm of the league, None for the realm the endpoints default to
+    pub realm: Option<TextFilter>,
+    pub sort: SortFilter,
+
+    // Uniques that share the base type of an unidentified unique
+    pub unique_candidates: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct TextFilter {
     pub text: String,
     pub enabled: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct PriceFilter {
     pub enabled: bool,
     pub option: String,
@@ -49,30 +65,148 @@ pub struct PriceFilter {
     pub max: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct RangeFilter {
     pub min: Option<f64>,
     pub max: Option<f64>,
     pub enabled: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StatFilter {
     pub id: String,
     pub text: String,
     pub enabled: bool,
     pub value: StatValue,
+    // Only used by weight groups
+    #[serde(default)]
+    pub weight: Option<f64>,
+    // Option of a stat imported from the trade site, e.g. the passive of "Allocates #"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub option: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum StatGroupType {
+    And,
+    Not,
+    Count,
+    Weight,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatGroup {
+    #[serde(rename = "type")]
+    pub group_type: StatGroupType,
+    pub filters: Vec<StatFilter>,
+    // Number of matching mods for count groups, or the weighted sum for weight groups
+    pub value: StatValue,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct StatValue {
     pub min: Option<f64>,
     pub max: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ToggleFilter {
-    pub enabled: bool,
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default, Clone)]
+pub struct OptionFilter<T> {
+    pub option: T,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ListingStatus {
+    // Instant buyout, or in person from sellers online in the league
+    Available,
+    // Instant buyout only
+    Securable,
+    // In person from sellers online in the league
+    OnlineLeague,
+    // In person from sellers online anywhere
+    #[default]
+    Online,
+    // Also stands in for statuses the trade site added that we don't know yet
+    #[serde(other)]
+    Any,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SaleType {
+    // Buyout or fixed price
+    Priced,
+    // Price given in the note
+    PricedWithInfo,
+    Unpriced,
+    // Also stands in for sale types we don't know, reported as dropped when importing
+    #[default]
+    #[serde(other)]
+    Any,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+pub enum ListedWithin {
+    #[serde(rename = "1hour")]
+    OneHour,
+    #[serde(rename = "3hours")]
+    ThreeHours,
+    #[serde(rename = "12hours")]
+    TwelveHours,
+    #[serde(rename = "1day")]
+    OneDay,
+    #[serde(rename = "3days")]
+    ThreeDays,
+    #[serde(rename = "1week")]
+    OneWeek,
+    #[serde(rename = "2weeks")]
+    TwoWeeks,
+    #[serde(rename = "1month")]
+    OneMonth,
+    #[serde(rename = "2months")]
+    TwoMonths,
+    // Also stands in for periods we don't know, reported as dropped when importing
+    #[default]
+    #[serde(rename = "any", other)]
+    Any,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    Price,
+    Dps,
+    Pdps,
+    Edps,
+    Stat,
+    ItemLevel,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct SortFilter {
+    pub key: SortKey,
+    // Trade stat id to sort by, only used with SortKey::Stat
+    pub stat_id: Option<String>,
+    pub direction: SortDirection,
+}
+
+impl Default for SortFilter {
+    fn default() -> Self {
+        Self {
+            key: SortKey::Price,
+            stat_id: None,
+            direction: SortDirection::Asc,
+        }
+    }
 }
 
 impl StatValue {
@@ -92,7 +226,21 @@ impl StatValue {
     }
 }
 
+impl Default for TradeFilters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TradeFilters {
+    // Category filter from the "Item Class: ..." header line
+    fn item_class_category(line: &str) -> Option<TextFilter> {
+        line.strip_prefix("Item Class: ").map(|class| TextFilter {
+            text: Self::map_item_category(class),
+            enabled: true,
+        })
+    }
+
     fn map_item_category(item_class: &str) -> String {
         let item_text = item_class.to_lowercase();
         match item_text.as_str() {
@@ -184,6 +332,7 @@ impl TradeFilters {
             total_dps: None,
             attack_speed: None,
             critical_chance: None,
+            reload_time: None,
             socket_count: None,
             armour: None,
             energy_shield: None,
@@ -194,27 +343,37 @@ impl TradeFilters {
             explicit_mods: Vec::new(),
             implicit_mods: Vec::new(),
             rune_mods: Vec::new(),
+            stat_groups: Vec::new(),
             price: PriceFilter {
                 enabled: true,
                 option: String::new(),
                 min: None,
                 max: None,
             },
-            online_only: ToggleFilter {
-                enabled: true,
-            },
+            status: OptionFilter::default(),
+            sale_type: OptionFilter::default(),
+            listed_within: OptionFilter::default(),
+            account: Some(TextFilter {
+                text: String::new(),
+                enabled: false,
+            }),
             league: Some(TextFilter {
                 text: "Standard".to_string(),
                 enabled: true,
             }),
+            realm: None,
+            sort: SortFilter::default(),
+            unique_candidates: Vec::new(),
         }
     }
 
     pub fn from_text(
         mod_pattern_to_trade_stat: impl Fn(&str, &str) -> Option<String>,
         item_text_to_base_type: impl Fn(&str) -> Option<(String, String)>,
+        unique_to_base_type: impl Fn(&str) -> Option<String>,
+        base_type_to_uniques: impl Fn(&str) -> Vec<String>,
         text: &str,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, TradeError> {
         let mut filters = Self::new();
         let mut avg_phys_dmg: Option<f64> = None;
         let mut avg_ele_dmg: Option<f64> = None;
@@ -222,8 +381,8 @@ impl TradeFilters {
 
         // Split on first separator
         let mut parts = text.splitn(2, "--------");
-        let header = parts.next().ok_or("Missing header section")?;
-        let body = parts.next().ok_or("Missing body section")?;
+        let header = parts.next().ok_or_else(|| TradeError::item_text("Missing header section"))?;
+        let body = parts.next().ok_or_else(|| TradeError::item_text("Missing body section"))?;
 
         let mod_text_to_trade_stat_and_values = |text: &str, prefix: &str| -> Option<(String, Vec<f64>)> {
             let (mod_pattern, values) = Self::mod_text_to_pattern(text);
@@ -238,7 +397,7 @@ impl TradeFilters {
             .collect();
 
         if header_lines.len() < 2 || header_lines.len() > 4 {
-            return Err("Invalid item format: header should be between 2 and 4 lines".to_string());
+            return Err(TradeError::item_text("Invalid item format: header should be between 2 and 4 lines"));
         }
 
         let rarity_line = if header_lines.len() == 2 {
@@ -249,7 +408,7 @@ impl TradeFilters {
 
         // Parse rarity
         let rarity = rarity_line.strip_prefix("Rarity: ")
-            .ok_or("Missing rarity")?;
+            .ok_or_else(|| TradeError::item_text("Missing rarity"))?;
         filters.rarity = Some(TextFilter {
             text: rarity.to_string().to_lowercase(),
             enabled: true,
@@ -272,20 +431,44 @@ impl TradeFilters {
                 }
             }
             "Unique" => {
-                // For unique items, we care about the name but not the base type or category
-                filters.item_name = Some(TextFilter {
-                    text: header_lines[2].to_string(),
-                    enabled: true,
-                });
-            }
-            "Rare" | "Magic" | "Normal" => {
-                // Parse item category
-                if let Some(class) = header_lines[0].strip_prefix("Item Class: ") {
-                    filters.item_category = Some(TextFilter {
-                        text: Self::map_item_category(class).to_string(),
+                filters.item_category = Self::item_class_category(header_lines[0]);
+                let name = *header_lines.get(2).ok_or_else(|| TradeError::item_text("Missing unique item name"))?;
+
+                if let Some(base_type) = unique_to_base_type(name) {
+                    filters.item_name = Some(TextFilter {
+                        text: name.to_string(),
                         enabled: true,
                     });
+                    filters.item_base_type = Some(TextFilter {
+                        text: base_type,
+                        enabled: true,
+                    });
+                } else {
+                    // Unidentified uniques only show their base type, so offer every unique using it
+                    let base_type = header_lines[header_lines.len() - 1];
+                    let candidates = base_type_to_uniques(base_type);
+
+                    if !candidates.is_empty() {
+                        filters.item_name = Some(TextFilter {
+                            text: String::new(),
+                            enabled: false,
+                        });
+                        filters.item_base_type = Some(TextFilter {
+                            text: base_type.to_string(),
+                            enabled: true,
+                        });
+                        filters.unique_candidates = candidates;
+                    } else {
+                        // Unknown unique (e.g. newer than our trade data), search by name only
+                        filters.item_name = Some(TextFilter {
+                            text: name.to_string(),
+                            enabled: true,
+                        });
+                    }
                 }
+            }
+            "Rare" | "Magic" | "Normal" => {
+                filters.item_category = Self::item_class_category(header_lines[0]);
 
                 if header_lines.len() == 4 {
                     filters.item_base_type = Some(TextFilter {
@@ -299,7 +482,7 @@ impl TradeFilters {
                     });
                 }
             }
-            _ => return Err(format!("Unsupported rarity: {}", rarity)),
+            _ => return Err(TradeError::item_text(format!("Unsupported rarity: {}", rarity))),
         }
 
         // Process body lines
@@ -316,7 +499,7 @@ impl TradeFilters {
                     .trim_end_matches('%')
                     .trim()
                     .parse()
-                    .map_err(|e| format!("Failed to parse waystone drop chance value: {}", e))?;
+                    .map_err(|e| TradeError::item_text(format!("Failed to parse waystone drop chance value: {}", e)))?;
                 filters.waystone_drop_chance = Some(RangeFilter {
                     min: Some(drop_chance),
                     max: None,
@@ -328,7 +511,7 @@ impl TradeFilters {
                     .trim_end_matches('%')
                     .trim()
                     .parse()
-                    .map_err(|e| format!("Failed to parse block chance value: {}", e))?;
+                    .map_err(|e| TradeError::item_text(format!("Failed to parse block chance value: {}", e)))?;
                 filters.block_chance = Some(RangeFilter {
                     min: Some(block),
                     max: None,
@@ -340,7 +523,7 @@ impl TradeFilters {
                     .next()
                     .unwrap_or("0")
                     .parse()
-                    .map_err(|e| format!("Failed to parse spirit value: {}", e))?;
+                    .map_err(|e| TradeError::item_text(format!("Failed to parse spirit value: {}", e)))?;
                 filters.spirit = Some(RangeFilter {
                     min: Some(spirit),
                     max: None,
@@ -356,7 +539,7 @@ impl TradeFilters {
             } else if let Some(level) = line.strip_prefix("Item Level: ") {
                 let level: f64 = level
                     .parse()
-                    .map_err(|e| format!("Failed to parse item level: {}", e))?;
+                    .map_err(|e| TradeError::item_text(format!("Failed to parse item level: {}", e)))?;
                 filters.item_level = Some(RangeFilter {
                     min: Some(level),
                     max: None,
@@ -368,7 +551,7 @@ impl TradeFilters {
                     .next()
                     .unwrap_or("0")
                     .parse()
-                    .map_err(|e| format!("Failed to parse armour value: {}", e))?;
+                    .map_err(|e| TradeError::item_text(format!("Failed to parse armour value: {}", e)))?;
                 filters.armour = Some(RangeFilter {
                     min: Some(armour),
                     max: None,
@@ -380,7 +563,7 @@ impl TradeFilters {
                     .next()
                     .unwrap_or("0")
                     .parse()
-                    .map_err(|e| format!("Failed to parse energy shield value: {}", e))?;
+                    .map_err(|e| TradeError::item_text(format!("Failed to parse energy shield value: {}", e)))?;
                 filters.energy_shield = Some(RangeFilter {
                     min: Some(es),
                     max: None,
@@ -392,7 +575,7 @@ impl TradeFilters {
                     .next()
                     .unwrap_or("0")
                     .parse()
-                    .map_err(|e| format!("Failed to parse evasion rating value: {}", e))?;
+                    .map_err(|e| TradeError::item_text(format!("Failed to parse evasion rating value: {}", e)))?;
                 filters.evasion = Some(RangeFilter {
                     min: Some(evasion),
                     max: None,
@@ -406,7 +589,7 @@ impl TradeFilters {
                     .next()
                     .unwrap_or("0")
                     .parse()
-                    .map_err(|e| format!("Failed to parse critical hit chance: {}", e))?;
+                    .map_err(|e| TradeError::item_text(format!("Failed to parse critical hit chance: {}", e)))?;
                 filters.critical_chance = Some(RangeFilter {
                     min: Some(crit),
                     max: None,
@@ -418,26 +601,39 @@ impl TradeFilters {
                         .next()
                         .unwrap_or("0")
                         .parse()
-                        .map_err(|e| format!("Failed to parse attack speed: {}", e))?,
+                        .map_err(|e| TradeError::item_text(format!("Failed to parse attack speed: {}", e)))?,
                 );
                 filters.attack_speed = Some(RangeFilter {
                     min: attack_speed,
                     max: None,
                     enabled: true,
                 });
+            } else if let Some(reload) = line.strip_prefix("Reload Time: ") {
+                // Lower is better, so search for reload times at most this long
+                let reload = reload
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or("0")
+                    .parse()
+                    .map_err(|e| TradeError::item_text(format!("Failed to parse reload time: {}", e)))?;
+                filters.reload_time = Some(RangeFilter {
+                    min: None,
+                    max: Some(reload),
+                    enabled: true,
+                });
             } else if let Some(phys_dmg) = line.strip_prefix("Physical Damage: ") {
                 let parts: Vec<&str> = phys_dmg.split('-').collect();
                 if parts.len() == 2 {
                     let min: f64 = parts[0]
                         .trim()
                         .parse()
-                        .map_err(|e| format!("Failed to parse physical damage min: {}", e))?;
+                        .map_err(|e| TradeError::item_text(format!("Failed to parse physical damage min: {}", e)))?;
                     let max: f64 = parts[1]
                         .split_whitespace()
                         .next()
                         .unwrap_or("0")
                         .parse()
-                        .map_err(|e| format!("Failed to parse physical damage max: {}", e))?;
+                        .map_err(|e| TradeError::item_text(format!("Failed to parse physical damage max: {}", e)))?;
                     avg_phys_dmg = Some((min + max) / 2.0);
                 }
             } else if let Some(ele_dmg) = line.strip_prefix("Elemental Damage: ") {
@@ -446,13 +642,13 @@ impl TradeFilters {
                     let min: f64 = parts[0]
                         .trim()
                         .parse()
-                        .map_err(|e| format!("Failed to parse elemental damage min: {}", e))?;
+                        .map_err(|e| TradeError::item_text(format!("Failed to parse elemental damage min: {}", e)))?;
                     let max: f64 = parts[1]
                         .split_whitespace()
                         .next()
                         .unwrap_or("0")
                         .parse()
-                        .map_err(|e| format!("Failed to parse elemental damage max: {}", e))?;
+                        .map_err(|e| TradeError::item_text(format!("Failed to parse elemental damage max: {}", e)))?;
                     avg_ele_dmg = Some((min + max) / 2.0);
                 }
             } else if line.ends_with("(implicit)") {
@@ -463,6 +659,8 @@ impl TradeFilters {
                         text: mod_text.to_string(),
                         enabled: true,
                         value: StatValue::from_values(&values),
+                        weight: None,
+                        option: None,
                     });
                 }
             } else if line.ends_with("(rune)") {
@@ -473,13 +671,14 @@ impl TradeFilters {
                         text: mod_text.to_string(),
                         enabled: true,
                         value: StatValue::from_values(&values),
+                        weight: None,
+                        option: None,
                     });
                 }
             } else if !line.starts_with("Requirements:")
                 && !line.starts_with("Level:")
                 && !line.starts_with("Str:")
                 && !line.starts_with("Dex:")
-                && !line.starts_with("Reload Time:")
                 && !line.starts_with("Sockets:")
                 && !line.starts_with("Quality:")
             {
@@ -490,6 +689,8 @@ impl TradeFilters {
                         text: line.to_string(),
                         enabled: true,
                         value: StatValue::from_values(&values),
+                        weight: None,
+                        option: None,
                     });
                 }
             }
@@ -633,6 +834,28 @@ mod test {
                         "flags": { "unique": false }
                     }
                 ]
+            },
+            {
+                "id": "accessory",
+                "label": "Accessories",
+                "entries": [
+                    {
+                        "type": "Gold Ring",
+                        "text": "Gold Ring"
+                    },
+                    {
+                        "name": "Andvarius",
+                        "type": "Gold Ring",
+                        "text": "Andvarius Gold Ring",
+                        "flags": { "unique": true }
+                    },
+                    {
+                        "name": "Ming's Heart",
+                        "type": "Gold Ring",
+                        "text": "Ming's Heart Gold Ring",
+                        "flags": { "unique": true }
+                    }
+                ]
             }]
         });
 
@@ -725,12 +948,41 @@ Item Level: 17
 +5 to Intelligence
 +8% to Cold Resistance"#;
 
+    const UNIQUE_TEST_ITEM: &str = r#"Item Class: Rings
+Rarity: Unique
+Andvarius
+Gold Ring
+--------
+Requirements:
+Level: 8
+--------
+Item Level: 60
+--------
++7% to Rarity of Items found (implicit)
+--------
+79% increased Rarity of Items found"#;
+
+    const UNIDENTIFIED_UNIQUE_TEST_ITEM: &str = r#"Item Class: Rings
+Rarity: Unique
+Gold Ring
+--------
+Requirements:
+Level: 8
+--------
+Item Level: 60
+--------
++7% to Rarity of Items found (implicit)
+--------
+Unidentified"#;
+
     #[test]
     fn test_trade_filters_from_text() {
         let (affix_map, base_type_map) = get_test_maps();
         let filters = TradeFilters::from_text(
             |text, prefix| affix_map.mod_pattern_to_trade_stat(text, prefix),
             |text| base_type_map.item_text_to_base_type(text),
+            |name| base_type_map.unique_to_base_type(name),
+            |base_type| base_type_map.base_type_to_uniques(base_type),
             TEST_ITEM,
         )
         .expect("Should parse successfully");
@@ -780,6 +1032,16 @@ Item Level: 17
             })
         );
 
+        // check reload time
+        assert_eq!(
+            filters.reload_time,
+            Some(RangeFilter {
+                min: None,
+                max: Some(0.75),
+                enabled: true,
+            })
+        );
+
         // Check implicit mods
         assert_eq!(filters.implicit_mods.len(), 1);
         let implicit_mod = &filters.implicit_mods[0];
@@ -817,6 +1079,8 @@ Item Level: 17
         let filters = TradeFilters::from_text(
             |text, prefix| affix_map.mod_pattern_to_trade_stat(text, prefix),
             |text| base_type_map.item_text_to_base_type(text),
+            |name| base_type_map.unique_to_base_type(name),
+            |base_type| base_type_map.base_type_to_uniques(base_type),
             TEST_ITEM_WITH_AILMENT,
         )
         .expect("Should parse successfully");
@@ -879,6 +1143,8 @@ Item Level: 17
         let filters = TradeFilters::from_text(
             |text, prefix| affix_map.mod_pattern_to_trade_stat(text, prefix),
             |text| base_type_map.item_text_to_base_type(text),
+            |name| base_type_map.unique_to_base_type(name),
+            |base_type| base_type_map.base_type_to_uniques(base_type),
             BOW_TEST_ITEM,
         )
         .expect("Should parse successfully");
@@ -1005,6 +1271,8 @@ Item Level: 17
         let filters = TradeFilters::from_text(
             |text, prefix| affix_map.mod_pattern_to_trade_stat(text, prefix),
             |text| base_type_map.item_text_to_base_type(text),
+            |name| base_type_map.unique_to_base_type(name),
+            |base_type| base_type_map.base_type_to_uniques(base_type),
             ARMOUR_TEST_ITEM,
         )
         .expect("Should parse successfully");
@@ -1059,4 +1327,77 @@ Item Level: 17
         // Check explicit mods (just verify count as the actual mod parsing is tested elsewhere)
         assert_eq!(filters.explicit_mods.len(), 4);
     }
+
+    #[test]
+    fn test_unique_items() {
+        let (affix_map, base_type_map) = get_test_maps();
+        let filters = TradeFilters::from_text(
+            |text, prefix| affix_map.mod_pattern_to_trade_stat(text, prefix),
+            |text| base_type_map.item_text_to_base_type(text),
+            |name| base_type_map.unique_to_base_type(name),
+            |base_type| base_type_map.base_type_to_uniques(base_type),
+            UNIQUE_TEST_ITEM,
+        )
+        .expect("Should parse successfully");
+
+        // Identified uniques get their base type from the unique index and category from the item class
+        assert_eq!(
+            filters.item_name,
+            Some(TextFilter {
+                text: "Andvarius".to_string(),
+                enabled: true,
+            })
+        );
+        assert_eq!(
+            filters.item_base_type,
+            Some(TextFilter {
+                text: "Gold Ring".to_string(),
+                enabled: true,
+            })
+        );
+        assert_eq!(
+            filters.item_category,
+            Some(TextFilter {
+                text: "accessory.ring".to_string(),
+                enabled: true,
+            })
+        );
+        assert!(filters.unique_candidates.is_empty());
+
+        let filters = TradeFilters::from_text(
+            |text, prefix| affix_map.mod_pattern_to_trade_stat(text, prefix),
+            |text| base_type_map.item_text_to_base_type(text),
+            |name| base_type_map.unique_to_base_type(name),
+            |base_type| base_type_map.base_type_to_uniques(base_type),
+            UNIDENTIFIED_UNIQUE_TEST_ITEM,
+        )
+        .expect("Should parse successfully");
+
+        // Unidentified uniques list every unique on the base and leave the name unset
+        assert_eq!(
+            filters.item_name,
+            Some(TextFilter {
+                text: String::new(),
+                enabled: false,
+            })
+        );
+        assert_eq!(
+            filters.item_base_type,
+            Some(TextFilter {
+                text: "Gold Ring".to_string(),
+                enabled: true,
+            })
+        );
+        assert_eq!(
+            filters.item_category,
+            Some(TextFilter {
+                text: "accessory.ring".to_string(),
+                enabled: true,
+            })
+        );
+        assert_eq!(
+            filters.unique_candidates,
+            vec!["Andvarius".to_string(), "Ming's Heart".to_string()]
+        );
+    }
 }
@@ -1,275 +1,664 @@
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::Value;
+use std::collections::BTreeMap;
 
-use super::trade_filter::TradeFilters;
+use super::trade_filter::{
+    ListedWithin, ListingStatus, OptionFilter, PriceFilter, RangeFilter, SaleType, SortDirection,
+    SortFilter, SortKey, StatFilter, StatGroup, StatGroupType, StatValue, TextFilter, TradeFilters,
+};
 
+/// Body of a trade search request
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TradeQuery {
-    pub query: Value,
-    pub sort: Value,
+    pub query: Query,
+    pub sort: BTreeMap<String, SortDirection>,
     pub league: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub realm: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Query {
+    #[serde(default)]
+    pub status: OptionValue<ListingStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub base_type: Option<String>,
+    #[serde(default)]
+    pub stats: Vec<StatGroupQuery>,
+    #[serde(default)]
+    pub filters: QueryFilters,
+    // Anything in an imported search that isn't modeled, kept only to report what gets dropped
+    #[serde(flatten, skip_serializing)]
+    pub other: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct OptionValue<T> {
+    pub option: T,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct RangeValue {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatGroupQuery {
+    #[serde(rename = "type")]
+    pub group_type: StatGroupType,
+    #[serde(default)]
+    pub filters: Vec<StatQuery>,
+    #[serde(default)]
+    pub disabled: bool,
+    // Only count and weight groups carry a group-level value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<StatValueQuery>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatQuery {
+    pub id: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<StatValueQuery>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct StatValueQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    // A flag for boolean mods, or an option id for mods like "Allocates #"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub option: Option<Value>,
+}
+
+/// A named group of filters, sent only when at least one of its filters is set
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FilterGroup<T> {
+    pub filters: T,
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+impl<T: Default + PartialEq> FilterGroup<T> {
+    fn from_filters(filters: T) -> Option<Self> {
+        (filters != T::default()).then_some(Self {
+            filters,
+            disabled: false,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct QueryFilters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_filters: Option<FilterGroup<TypeFilters>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equipment_filters: Option<FilterGroup<EquipmentFilters>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub misc_filters: Option<FilterGroup<MiscFilters>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map_filters: Option<FilterGroup<MapFilters>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trade_filters: Option<FilterGroup<TradeFilterGroup>>,
+    // Unmodeled filters, see `Query::other`
+    #[serde(flatten, skip_serializing)]
+    pub other: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct TypeFilters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<OptionValue<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rarity: Option<OptionValue<String>>,
+    // Unmodeled filters, see `Query::other`
+    #[serde(flatten, skip_serializing)]
+    pub other: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct EquipmentFilters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pdps: Option<RangeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edps: Option<RangeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dps: Option<RangeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aps: Option<RangeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crit: Option<RangeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reload_time: Option<RangeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rune_sockets: Option<RangeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ar: Option<RangeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub es: Option<RangeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ev: Option<RangeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spirit: Option<RangeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<RangeValue>,
+    // Unmodeled filters, see `Query::other`
+    #[serde(flatten, skip_serializing)]
+    pub other: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct MiscFilters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ilvl: Option<RangeValue>,
+    // Unmodeled filters, see `Query::other`
+    #[serde(flatten, skip_serializing)]
+    pub other: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct MapFilters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map_bonus: Option<RangeValue>,
+    // Unmodeled filters, see `Query::other`
+    #[serde(flatten, skip_serializing)]
+    pub other: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct TradeFilterGroup {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collapse: Option<OptionValue<bool>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexed: Option<OptionValue<ListedWithin>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sale_type: Option<OptionValue<SaleType>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<AccountValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<PriceValue>,
+    // Unmodeled filters, see `Query::other`
+    #[serde(flatten, skip_serializing)]
+    pub other: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AccountValue {
+    pub input: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PriceValue {
+    #[serde(default)]
+    pub option: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
 }
 
 impl TradeQuery {
-    pub fn new(query: Value, league: String) -> Self {
+    pub fn from_trade_filters(filters: &TradeFilters) -> Self {
+        // Explicit mods without a range match any roll, implicit and rune mods without one
+        // are flag mods that need the option set
+        let stat_filters = filters
+            .explicit_mods
+            .iter()
+            .filter(|stat| stat.enabled)
+            .map(|stat| stat_to_query(stat, false))
+            .chain(
+                filters
+                    .implicit_mods
+                    .iter()
+                    .chain(&filters.rune_mods)
+                    .filter(|stat| stat.enabled)
+                    .map(|stat| stat_to_query(stat, true)),
+            )
+            .collect();
+
+        let mut stats = vec![StatGroupQuery {
+            group_type: StatGroupType::And,
+            filters: stat_filters,
+            disabled: false,
+            value: None,
+        }];
+
+        // Add any extra stat groups (and/not/count/weight)
+        for group in &filters.stat_groups {
+            if group.enabled && group.filters.iter().any(|stat| stat.enabled) {
+                stats.push(stat_group_to_query(group));
+            }
+        }
+
+        let type_filters = TypeFilters {
+            category: enabled_text(&filters.item_category).map(|category| OptionValue {
+                option: category.to_lowercase(),
+            }),
+            rarity: enabled_text(&filters.rarity).map(|rarity| OptionValue {
+                option: rarity.to_lowercase(),
+            }),
+            other: BTreeMap::new(),
+        };
+
+        let equipment_filters = EquipmentFilters {
+            pdps: enabled_range(&filters.physical_dps),
+            edps: enabled_range(&filters.elemental_dps),
+            dps: enabled_range(&filters.total_dps),
+            aps: enabled_range(&filters.attack_speed),
+            crit: enabled_range(&filters.critical_chance),
+            reload_time: enabled_range(&filters.reload_time),
+            rune_sockets: enabled_range(&filters.socket_count),
+            ar: enabled_range(&filters.armour),
+            es: enabled_range(&filters.energy_shield),
+            ev: enabled_range(&filters.evasion),
+            spirit: enabled_range(&filters.spirit),
+            block: enabled_range(&filters.block_chance),
+            other: BTreeMap::new(),
+        };
+
+        let misc_filters = MiscFilters {
+            ilvl: enabled_range(&filters.item_level),
+            other: BTreeMap::new(),
+        };
+
+        let map_filters = MapFilters {
+            map_bonus: enabled_range(&filters.waystone_drop_chance),
+            other: BTreeMap::new(),
+        };
+
+        let trade_filters = TradeFilterGroup {
+            collapse: Some(OptionValue { option: true }),
+            indexed: (filters.listed_within.option != ListedWithin::Any).then_some(OptionValue {
+                option: filters.listed_within.option,
+            }),
+            sale_type: (filters.sale_type.option != SaleType::Any).then_some(OptionValue {
+                option: filters.sale_type.option,
+            }),
+            account: enabled_text(&filters.account).map(|account| AccountValue {
+                input: account.to_string(),
+            }),
+            price: (filters.price.enabled && !filters.price.option.is_empty()).then(|| PriceValue {
+                option: filters.price.option.clone(),
+                min: filters.price.min,
+                max: filters.price.max,
+            }),
+            other: BTreeMap::new(),
+        };
+
+        let query = Query {
+            status: OptionValue {
+                option: filters.status.option,
+            },
+            name: enabled_text(&filters.item_name).map(str::to_string),
+            base_type: enabled_text(&filters.item_base_type).map(str::to_string),
+            stats,
+            filters: QueryFilters {
+                type_filters: FilterGroup::from_filters(type_filters),
+                equipment_filters: FilterGroup::from_filters(equipment_filters),
+                misc_filters: FilterGroup::from_filters(misc_filters),
+                map_filters: FilterGroup::from_filters(map_filters),
+                trade_filters: FilterGroup::from_filters(trade_filters),
+                other: BTreeMap::new(),
+            },
+            other: BTreeMap::new(),
+        };
+
+        let league = filters.league.as_ref()
+            .map(|l| l.text.clone())
+            .unwrap_or_else(|| "Standard".to_string());
+
         Self {
             query,
-            sort: json!({ "price": "asc" }),
+            sort: sort_to_query(&filters.sort),
             league,
+            realm: filters.realm.as_ref().map(|realm| realm.text.clone()),
         }
     }
 
-    pub fn from_trade_filters(filters: &TradeFilters) -> Self {
-        let mut stat_filters = Vec::new();
-
-        // Add explicit mods
-        for stat in &filters.explicit_mods {
-            if stat.enabled {
-                if stat.value.min.is_some() || stat.value.max.is_some() {
-                    stat_filters.push(json!({
-                        "id": stat.id,
-                        "disabled": false,
-                        "value": {
-                            "min": stat.value.min,
-                            "max": stat.value.max
-                        }
-                    }));
-                } else {
-                    stat_filters.push(json!({
-                        "id": stat.id,
-                        "disabled": false
-                    }));
-                }
-            }
-        }
+    /// Parses a search shared from the trade site, either a full request body or just its
+    /// `query` object. Neither contains the league or realm, so they have to be passed in.
+    pub fn from_search_json(json: Value, realm: Option<String>, league: String) -> Result<Self, String> {
+        let (query, sort) = match json.get("query") {
+            Some(query) => (query.clone(), json.get("sort").cloned()),
+            None => (json, None),
+        };
 
-        // Add implicit mods
-        for stat in &filters.implicit_mods {
-            if stat.enabled {
-                if stat.value.min.is_some() || stat.value.max.is_some() {
-                    stat_filters.push(json!({
-                        "id": stat.id,
-                        "disabled": false,
-                        "value": {
-                            "min": stat.value.min,
-                            "max": stat.value.max
-                        }
-                    }));
-                } else {
-                    stat_filters.push(json!({
-                        "id": stat.id,
-                        "disabled": false,
-                        "value": {
-                            "option": true
-                        }
-                    }));
-                }
-            }
-        }
+        let query = serde_json::from_value(query)
+            .map_err(|e| format!("Failed to parse trade query: {}", e))?;
+        let sort = match sort {
+            Some(sort) => serde_json::from_value(sort)
+                .map_err(|e| format!("Failed to parse trade sort: {}", e))?,
+            None => BTreeMap::new(),
+        };
 
-        // Add rune mods
-        for stat in &filters.rune_mods {
-            if stat.enabled {
-                if stat.value.min.is_some() || stat.value.max.is_some() {
-                    stat_filters.push(json!({
-                        "id": stat.id,
-                        "disabled": false,
-                        "value": {
-                            "min": stat.value.min,
-                            "max": stat.value.max
-                        }
-                    }));
-                } else {
-                    stat_filters.push(json!({
-                        "id": stat.id,
-                        "disabled": false,
-                        "value": {
-                            "option": true
-                        }
-                    }));
+        Ok(Self {
+            query,
+            sort,
+            league,
+            realm,
+        })
+    }
+
+    /// The reverse of `from_trade_filters`, used to edit searches imported from the trade site
+    pub fn to_trade_filters(&self, trade_stat_to_text: impl Fn(&str) -> Option<String>) -> TradeFilters {
+        let query = &self.query;
+        let mut filters = TradeFilters::new();
+
+        filters.status = OptionFilter {
+            option: query.status.option,
+        };
+        filters.item_name = query.name.as_deref().map(enabled_text_filter);
+        filters.item_base_type = query.base_type.as_deref().map(enabled_text_filter);
+
+        for (i, group) in query.stats.iter().enumerate() {
+            let stat_filters = group
+                .filters
+                .iter()
+                .map(|stat| stat_from_query(stat, group.disabled, &trade_stat_to_text));
+
+            // The leading "and" group holds the item's own mods, any other group is kept as is
+            if i == 0 && group.group_type == StatGroupType::And {
+                for stat in stat_filters {
+                    match stat.id.split('.').next() {
+                        Some("implicit") => filters.implicit_mods.push(stat),
+                        Some("rune") => filters.rune_mods.push(stat),
+                        _ => filters.explicit_mods.push(stat),
+                    }
                 }
+            } else {
+                let value = group.value.as_ref();
+                filters.stat_groups.push(StatGroup {
+                    group_type: group.group_type,
+                    filters: stat_filters.collect(),
+                    value: StatValue {
+                        min: value.and_then(|value| value.min),
+                        max: value.and_then(|value| value.max),
+                    },
+                    enabled: !group.disabled,
+                });
             }
         }
 
-        // Build the main query
-        let mut query = json!({
-            "status": {
-                "option": if filters.online_only.enabled { "online" } else { "any" }
-            },
-            "stats": [{
-                "type": "and",
-                "filters": stat_filters,
-                "disabled": false
-            }]
-        });
-
-        // Only add type filters if we have an enabled category
-        if filters.item_category.as_ref().map_or(false, |c| c.enabled) {
-            query["filters"]["type_filters"] = json!({
-                "filters": {
-                    "category": {
-                        "option": filters.item_category.as_ref()
-                            .map(|c| c.text.to_lowercase())
-                            .unwrap_or_default()
-                    }
-                },
-                "disabled": false
+        if let Some(group) = &query.filters.type_filters {
+            let enabled = !group.disabled;
+            filters.item_category = group.filters.category.as_ref().map(|category| TextFilter {
+                text: category.option.clone(),
+                enabled,
+            });
+            filters.rarity = group.filters.rarity.as_ref().map(|rarity| TextFilter {
+                text: rarity.option.clone(),
+                enabled,
             });
         }
 
-        // Add name filter if enabled (for unique items)
-        if let Some(name) = &filters.item_name {
-            if name.enabled {
-                query["name"] = json!(name.text);
-            }
-        }
-        // Add type filter if enabled (for non-unique items)
-        else if let Some(base_type) = &filters.item_base_type {
-            if base_type.enabled {
-                query["type"] = json!(base_type.text);
-            }
+        if let Some(group) = &query.filters.equipment_filters {
+            let enabled = !group.disabled;
+            let equipment = &group.filters;
+            filters.physical_dps = range_from_query(&equipment.pdps, enabled);
+            filters.elemental_dps = range_from_query(&equipment.edps, enabled);
+            filters.total_dps = range_from_query(&equipment.dps, enabled);
+            filters.attack_speed = range_from_query(&equipment.aps, enabled);
+            filters.critical_chance = range_from_query(&equipment.crit, enabled);
+            filters.reload_time = range_from_query(&equipment.reload_time, enabled);
+            filters.socket_count = range_from_query(&equipment.rune_sockets, enabled);
+            filters.armour = range_from_query(&equipment.ar, enabled);
+            filters.energy_shield = range_from_query(&equipment.es, enabled);
+            filters.evasion = range_from_query(&equipment.ev, enabled);
+            filters.spirit = range_from_query(&equipment.spirit, enabled);
+            filters.block_chance = range_from_query(&equipment.block, enabled);
         }
 
-        // Add item level filter if present and enabled
-        if let Some(ilvl) = &filters.item_level {
-            if ilvl.enabled {
-                query["filters"]["misc_filters"] = json!({
-                    "filters": {
-                        "ilvl": {
-                            "min": ilvl.min,
-                            "max": ilvl.max
-                        }
-                    },
-                    "disabled": false
-                });
-            }
+        if let Some(group) = &query.filters.misc_filters {
+            filters.item_level = range_from_query(&group.filters.ilvl, !group.disabled);
         }
 
-        // Add equipment filters if any are present and enabled
-        let mut equipment_filters = json!({});
-        let mut has_equipment_filters = false;
-
-        if let Some(rarity) = &filters.rarity {
-            if rarity.enabled && rarity.text != "" {
-                equipment_filters["rarity"] = json!(rarity.text);
-                has_equipment_filters = true;
-            }
+        if let Some(group) = &query.filters.map_filters {
+            filters.waystone_drop_chance = range_from_query(&group.filters.map_bonus, !group.disabled);
         }
 
-        if let Some(pdps) = &filters.physical_dps {
-            if pdps.enabled {
-                equipment_filters["pdps"] = json!({"min": pdps.min, "max": pdps.max});
-                has_equipment_filters = true;
+        if let Some(group) = &query.filters.trade_filters {
+            let trade = &group.filters;
+            if let Some(indexed) = &trade.indexed {
+                filters.listed_within = OptionFilter {
+                    option: indexed.option,
+                };
             }
-        }
-        if let Some(edps) = &filters.elemental_dps {
-            if edps.enabled {
-                equipment_filters["edps"] = json!({"min": edps.min, "max": edps.max});
-                has_equipment_filters = true;
+            if let Some(sale_type) = &trade.sale_type {
+                filters.sale_type = OptionFilter {
+                    option: sale_type.option,
+                };
             }
-        }
-        if let Some(dps) = &filters.total_dps {
-            if dps.enabled {
-                equipment_filters["dps"] = json!({"min": dps.min, "max": dps.max});
-                has_equipment_filters = true;
+            if let Some(account) = &trade.account {
+                filters.account = Some(TextFilter {
+                    text: account.input.clone(),
+                    enabled: !group.disabled,
+                });
             }
-        }
-        if let Some(aps) = &filters.attack_speed {
-            if aps.enabled {
-                equipment_filters["aps"] = json!({"min": aps.min, "max": aps.max});
-                has_equipment_filters = true;
+            if let Some(price) = &trade.price {
+                filters.price = PriceFilter {
+                    enabled: !group.disabled,
+                    option: price.option.clone(),
+                    min: price.min,
+                    max: price.max,
+                };
             }
         }
-        if let Some(sockets) = &filters.socket_count {
-            if sockets.enabled {
-                equipment_filters["rune_sockets"] = json!({"min": sockets.min, "max": sockets.max});
-                has_equipment_filters = true;
-            }
+
+        filters.league = Some(enabled_text_filter(&self.league));
+        filters.realm = self.realm.as_deref().map(enabled_text_filter);
+        if let Some(sort) = sort_from_query(&self.sort) {
+            filters.sort = sort;
         }
 
-        // Add armour filters
-        if let Some(armour) = &filters.armour {
-            if armour.enabled {
-                equipment_filters["ar"] = json!({"min": armour.min, "max": armour.max});
-                has_equipment_filters = true;
-            }
+        filters
+    }
+
+    /// Parts of an imported search that `to_trade_filters` can't carry over, e.g.
+    /// `req_filters` or `trade_filters.indexed`, so the user can be told what's missing
+    pub fn dropped_filters(&self) -> Vec<String> {
+        let query = &self.query;
+        let filters = &query.filters;
+        let mut dropped = query.other.keys().chain(filters.other.keys()).cloned().collect::<Vec<_>>();
+
+        let unmodeled = |group: &str, other: &BTreeMap<String, Value>| {
+            other.keys().map(|key| format!("{}.{}", group, key)).collect::<Vec<_>>()
+        };
+        if let Some(group) = &filters.type_filters {
+            dropped.extend(unmodeled("type_filters", &group.filters.other));
         }
-        if let Some(es) = &filters.energy_shield {
-            if es.enabled {
-                equipment_filters["es"] = json!({"min": es.min, "max": es.max});
-                has_equipment_filters = true;
-            }
+        if let Some(group) = &filters.equipment_filters {
+            dropped.extend(unmodeled("equipment_filters", &group.filters.other));
         }
-        if let Some(evasion) = &filters.evasion {
-            if evasion.enabled {
-                equipment_filters["ev"] = json!({"min": evasion.min, "max": evasion.max});
-                has_equipment_filters = true;
-            }
+        if let Some(group) = &filters.misc_filters {
+            dropped.extend(unmodeled("misc_filters", &group.filters.other));
         }
-        if let Some(spirit) = &filters.spirit {
-            if spirit.enabled {
-                equipment_filters["spirit"] = json!({"min": spirit.min, "max": spirit.max});
-                has_equipment_filters = true;
-            }
+        if let Some(group) = &filters.map_filters {
+            dropped.extend(unmodeled("map_filters", &group.filters.other));
         }
-        if let Some(block) = &filters.block_chance {
-            if block.enabled {
-                equipment_filters["block"] = json!({"min": block.min, "max": block.max});
-                has_equipment_filters = true;
+        if let Some(group) = &filters.trade_filters {
+            let trade = &group.filters;
+            dropped.extend(unmodeled("trade_filters", &trade.other));
+
+            // Unknown values parse as "any", which the trade site leaves out instead of sending
+            if trade.indexed.as_ref().is_some_and(|indexed| indexed.option == ListedWithin::Any) {
+                dropped.push("trade_filters.indexed".to_string());
+            }
+            if trade.sale_type.as_ref().is_some_and(|sale_type| sale_type.option == SaleType::Any) {
+                dropped.push("trade_filters.sale_type".to_string());
             }
         }
 
-        if has_equipment_filters {
-            query["filters"]["equipment_filters"] = json!({
-                "filters": equipment_filters,
-                "disabled": false
-            });
-        }
+        dropped
+    }
+}
 
-        if let Some(drop_chance) = &filters.waystone_drop_chance {
-            if drop_chance.enabled {
-                query["filters"]["map_filters"] = json!({
-                    "filters": {
-                        "map_bonus": {
-                            "min": drop_chance.min,
-                            "max": drop_chance.max
-                        }
-                    },
-                    "disabled": false
-                });
-            }
-        }
+// Text of an enabled, non-empty text filter
+fn enabled_text(filter: &Option<TextFilter>) -> Option<&str> {
+    filter
+        .as_ref()
+        .filter(|filter| filter.enabled && !filter.text.is_empty())
+        .map(|filter| filter.text.as_str())
+}
 
-        let mut trade_filters = json!({});
-        trade_filters["collapse"] = json!({ "option": true });
+fn enabled_range(filter: &Option<RangeFilter>) -> Option<RangeValue> {
+    filter
+        .as_ref()
+        .filter(|filter| filter.enabled)
+        .map(|filter| RangeValue {
+            min: filter.min,
+            max: filter.max,
+        })
+}
 
-        if filters.price.enabled && !filters.price.option.is_empty() {
-            trade_filters["price"] = json!({
-                "option": filters.price.option,
-                "min": filters.price.min,
-                "max": filters.price.max
-            });
-        }
+fn stat_to_query(stat: &StatFilter, is_flag: bool) -> StatQuery {
+    let has_range = stat.value.min.is_some() || stat.value.max.is_some();
+    let option = stat
+        .option
+        .clone()
+        .or_else(|| (is_flag && !has_range).then_some(Value::Bool(true)));
+    let value = (has_range || option.is_some()).then(|| StatValueQuery {
+        min: stat.value.min,
+        max: stat.value.max,
+        option,
+        ..Default::default()
+    });
 
-        query["filters"]["trade_filters"] = json!({
-            "filters": trade_filters,
-            "disabled": false
-        });
+    StatQuery {
+        id: stat.id.clone(),
+        disabled: false,
+        value,
+    }
+}
 
-        let league = filters.league.as_ref()
-            .map(|l| l.text.clone())
-            .unwrap_or_else(|| "Standard".to_string());
-        Self::new(query, league)
+fn enabled_text_filter(text: &str) -> TextFilter {
+    TextFilter {
+        text: text.to_string(),
+        enabled: true,
+    }
+}
+
+fn range_from_query(value: &Option<RangeValue>, enabled: bool) -> Option<RangeFilter> {
+    value.as_ref().map(|value| RangeFilter {
+        min: value.min,
+        max: value.max,
+        enabled,
+    })
+}
+
+fn stat_from_query(
+    stat: &StatQuery,
+    group_disabled: bool,
+    trade_stat_to_text: impl Fn(&str) -> Option<String>,
+) -> StatFilter {
+    let value = stat.value.as_ref();
+    StatFilter {
+        id: stat.id.clone(),
+        // Fall back to the id so unknown stats are still shown and searched for
+        text: trade_stat_to_text(&stat.id).unwrap_or_else(|| stat.id.clone()),
+        enabled: !stat.disabled && !group_disabled,
+        value: StatValue {
+            min: value.and_then(|value| value.min),
+            max: value.and_then(|value| value.max),
+        },
+        weight: value.and_then(|value| value.weight),
+        option: value.and_then(|value| value.option.clone()),
+    }
+}
+
+fn sort_from_query(sort: &BTreeMap<String, SortDirection>) -> Option<SortFilter> {
+    let (key, direction) = sort.iter().next()?;
+    let (key, stat_id) = match key.as_str() {
+        "price" => (SortKey::Price, None),
+        "dps" => (SortKey::Dps, None),
+        "pdps" => (SortKey::Pdps, None),
+        "edps" => (SortKey::Edps, None),
+        "ilvl" => (SortKey::ItemLevel, None),
+        key => (SortKey::Stat, Some(key.strip_prefix("stat.")?.to_string())),
+    };
+
+    Some(SortFilter {
+        key,
+        stat_id,
+        direction: *direction,
+    })
+}
+
+fn sort_to_query(sort: &SortFilter) -> BTreeMap<String, SortDirection> {
+    let key = match (sort.key, &sort.stat_id) {
+        (SortKey::Price, _) => "price".to_string(),
+        (SortKey::Dps, _) => "dps".to_string(),
+        (SortKey::Pdps, _) => "pdps".to_string(),
+        (SortKey::Edps, _) => "edps".to_string(),
+        (SortKey::ItemLevel, _) => "ilvl".to_string(),
+        (SortKey::Stat, Some(stat_id)) => format!("stat.{}", stat_id),
+        // A stat sort without a stat can't be honoured, fall back to price
+        (SortKey::Stat, None) => "price".to_string(),
+    };
+
+    BTreeMap::from([(key, sort.direction)])
+}
+
+fn stat_group_to_query(group: &StatGroup) -> StatGroupQuery {
+    let filters = group
+        .filters
+        .iter()
+        .filter(|stat| stat.enabled)
+        .map(|stat| StatQuery {
+            id: stat.id.clone(),
+            disabled: false,
+            value: Some(StatValueQuery {
+                min: stat.value.min,
+                max: stat.value.max,
+                weight: (group.group_type == StatGroupType::Weight)
+                    .then(|| stat.weight.unwrap_or(1.0)),
+                option: stat.option.clone(),
+            }),
+        })
+        .collect();
+
+    // Count groups limit the number of matching mods, weight groups the weighted sum
+    let value = matches!(group.group_type, StatGroupType::Count | StatGroupType::Weight)
+        .then(|| stat_value_to_query(&group.value));
+
+    StatGroupQuery {
+        group_type: group.group_type,
+        filters,
+        disabled: false,
+        value,
+    }
+}
+
+fn stat_value_to_query(value: &StatValue) -> StatValueQuery {
+    StatValueQuery {
+        min: value.min,
+        max: value.max,
+        ..Default::default()
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::model::trade_filter::{
-        RangeFilter, StatFilter, StatValue, TextFilter, TradeFilters, ToggleFilter,
+        ListedWithin, ListingStatus, OptionFilter, RangeFilter, SaleType, SortDirection, SortFilter,
+        SortKey, StatFilter, StatGroup, StatGroupType, StatValue, TextFilter, TradeFilters,
     };
     use crate::model::trade_query::TradeQuery;
 
+    fn query_json(filters: &TradeFilters) -> serde_json::Value {
+        serde_json::to_value(TradeQuery::from_trade_filters(filters)).unwrap()
+    }
+
     fn assert_json_float_eq(value: &serde_json::Value, expected: f64) {
         let actual = value.as_f64().unwrap();
         let epsilon = 0.000001;
@@ -288,8 +677,8 @@ mod test {
             text: "weapon.crossbow".to_string(),
             enabled: true,
         });
-        filters.online_only = ToggleFilter {
-            enabled: true,
+        filters.status = OptionFilter {
+            option: ListingStatus::Online,
         };
 
         // Add an explicit mod with range values
@@ -301,6 +690,8 @@ mod test {
                 max: Some(115.5),
             },
             enabled: true,
+            weight: None,
+            option: None,
         });
 
         // Add an implicit mod with boolean value
@@ -312,6 +703,8 @@ mod test {
                 max: Some(1.0),
             },
             enabled: true,
+            weight: None,
+            option: None,
         });
 
         // Add some weapon properties
@@ -321,13 +714,13 @@ mod test {
             enabled: true,
         });
 
-        let query = TradeQuery::from_trade_filters(&filters);
+        let query = query_json(&filters);
 
         // Verify the query structure
-        assert_eq!(query.query["status"]["option"], "online");
+        assert_eq!(query["query"]["status"]["option"], "online");
 
         // Check stats section
-        let stats = &query.query["stats"][0];
+        let stats = &query["query"]["stats"][0];
         assert_eq!(stats["type"], "and");
         assert_eq!(stats["disabled"], false);
 
@@ -352,7 +745,7 @@ mod test {
         assert_eq!(grenade_filter["disabled"], false);
 
         // Check type filters
-        let type_filters = &query.query["filters"]["type_filters"];
+        let type_filters = &query["query"]["filters"]["type_filters"];
         assert_eq!(type_filters["disabled"], false);
         assert_eq!(
             type_filters["filters"]["category"]["option"],
@@ -360,13 +753,13 @@ mod test {
         );
 
         // Check weapon filters
-        let equipment_filters = &query.query["filters"]["equipment_filters"];
+        let equipment_filters = &query["query"]["filters"]["equipment_filters"];
         assert_eq!(equipment_filters["disabled"], false);
         assert_json_float_eq(&equipment_filters["filters"]["aps"]["min"], 1.85);
         assert_json_float_eq(&equipment_filters["filters"]["aps"]["max"], 1.85);
 
         // Check sort
-        assert_eq!(query.sort["price"], "asc");
+        assert_eq!(query["sort"]["price"], "asc");
     }
 
     #[test]
@@ -376,8 +769,8 @@ mod test {
             text: "armour.helmet".to_string(),
             enabled: true,
         });
-        filters.online_only = ToggleFilter {
-            enabled: true,
+        filters.status = OptionFilter {
+            option: ListingStatus::Online,
         };
 
         // Add armour properties
@@ -397,13 +790,13 @@ mod test {
             enabled: true,
         });
 
-        let query = TradeQuery::from_trade_filters(&filters);
+        let query = query_json(&filters);
 
         // Verify the query structure
-        assert_eq!(query.query["status"]["option"], "online");
+        assert_eq!(query["query"]["status"]["option"], "online");
 
         // Check type filters
-        let type_filters = &query.query["filters"]["type_filters"];
+        let type_filters = &query["query"]["filters"]["type_filters"];
         assert_eq!(type_filters["disabled"], false);
         assert_eq!(
             type_filters["filters"]["category"]["option"],
@@ -411,7 +804,7 @@ mod test {
         );
 
         // Check equipment filters
-        let equipment_filters = &query.query["filters"]["equipment_filters"];
+        let equipment_filters = &query["query"]["filters"]["equipment_filters"];
         assert_eq!(equipment_filters["disabled"], false);
 
         // Check armour values
@@ -426,4 +819,530 @@ mod test {
         assert_json_float_eq(&equipment_filters["filters"]["ev"]["min"], 919.0);
         assert!(equipment_filters["filters"]["ev"]["max"].is_null());
     }
+
+    #[test]
+    fn test_stat_groups_to_query() {
+        let resistance = |id: &str, weight: f64| StatFilter {
+            id: id.to_string(),
+            text: String::new(),
+            enabled: true,
+            value: StatValue {
+                min: None,
+                max: None,
+            },
+            weight: Some(weight),
+            option: None,
+        };
+
+        let mut filters = TradeFilters::new();
+        filters.stat_groups.push(StatGroup {
+            group_type: StatGroupType::Weight,
+            filters: vec![
+                resistance("explicit.stat_3372524247", 1.0),
+                resistance("explicit.stat_4220027924", 1.0),
+                resistance("explicit.stat_2923486259", 2.0),
+            ],
+            value: StatValue {
+                min: Some(100.0),
+                max: None,
+            },
+            enabled: true,
+        });
+        filters.stat_groups.push(StatGroup {
+            group_type: StatGroupType::Count,
+            filters: vec![
+                resistance("explicit.stat_803737631", 1.0),
+                resistance("explicit.stat_210067635", 1.0),
+            ],
+            value: StatValue {
+                min: Some(1.0),
+                max: None,
+            },
+            enabled: true,
+        });
+        filters.stat_groups.push(StatGroup {
+            group_type: StatGroupType::Not,
+            filters: vec![resistance("explicit.stat_669069897", 1.0)],
+            value: StatValue {
+                min: None,
+                max: None,
+            },
+            enabled: true,
+        });
+        // Disabled groups are left out
+        filters.stat_groups.push(StatGroup {
+            group_type: StatGroupType::Count,
+            filters: vec![resistance("explicit.stat_1509134228", 1.0)],
+            value: StatValue {
+                min: Some(1.0),
+                max: None,
+            },
+            enabled: false,
+        });
+
+        let query = query_json(&filters);
+
+        let stats = query["query"]["stats"].as_array().unwrap();
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats[0]["type"], "and");
+
+        let weight = &stats[1];
+        assert_eq!(weight["type"], "weight");
+        assert_json_float_eq(&weight["value"]["min"], 100.0);
+        assert_eq!(weight["filters"].as_array().unwrap().len(), 3);
+        assert_json_float_eq(&weight["filters"][2]["value"]["weight"], 2.0);
+
+        let count = &stats[2];
+        assert_eq!(count["type"], "count");
+        assert_json_float_eq(&count["value"]["min"], 1.0);
+        assert!(count["filters"][0]["value"].get("weight").is_none());
+
+        let not = &stats[3];
+        assert_eq!(not["type"], "not");
+        assert!(not.get("value").is_none());
+        assert_eq!(not["filters"][0]["id"], "explicit.stat_669069897");
+    }
+
+    #[test]
+    fn test_sort_to_query() {
+        let mut filters = TradeFilters::new();
+
+        filters.sort = SortFilter {
+            key: SortKey::Dps,
+            stat_id: None,
+            direction: SortDirection::Desc,
+        };
+        let query = query_json(&filters);
+        assert_eq!(query["sort"], serde_json::json!({ "dps": "desc" }));
+
+        filters.sort = SortFilter {
+            key: SortKey::Stat,
+            stat_id: Some("explicit.stat_803737631".to_string()),
+            direction: SortDirection::Desc,
+        };
+        let query = query_json(&filters);
+        assert_eq!(
+            query["sort"],
+            serde_json::json!({ "stat.explicit.stat_803737631": "desc" })
+        );
+
+        filters.sort = SortFilter {
+            key: SortKey::ItemLevel,
+            stat_id: None,
+            direction: SortDirection::Asc,
+        };
+        let query = query_json(&filters);
+        assert_eq!(query["sort"], serde_json::json!({ "ilvl": "asc" }));
+    }
+
+    #[test]
+    fn test_status_and_sale_type_to_query() {
+        let mut filters = TradeFilters::new();
+
+        // Defaults keep the previous "online" behaviour and leave sale type out
+        let query = query_json(&filters);
+        assert_eq!(query["query"]["status"]["option"], "online");
+        assert!(query["query"]["filters"]["trade_filters"]["filters"]
+            .get("sale_type")
+            .is_none());
+
+        filters.status = OptionFilter {
+            option: ListingStatus::Securable,
+        };
+        filters.sale_type = OptionFilter {
+            option: SaleType::Priced,
+        };
+        let query = query_json(&filters);
+        assert_eq!(query["query"]["status"]["option"], "securable");
+        assert_eq!(
+            query["query"]["filters"]["trade_filters"]["filters"]["sale_type"]["option"],
+            "priced"
+        );
+
+        filters.status = OptionFilter {
+            option: ListingStatus::OnlineLeague,
+        };
+        let query = query_json(&filters);
+        assert_eq!(query["query"]["status"]["option"], "onlineleague");
+    }
+
+    #[test]
+    fn test_listed_within_to_query() {
+        let mut filters = TradeFilters::new();
+
+        let query = query_json(&filters);
+        assert!(query["query"]["filters"]["trade_filters"]["filters"]
+            .get("indexed")
+            .is_none());
+
+        filters.listed_within = OptionFilter {
+            option: ListedWithin::ThreeDays,
+        };
+        let query = query_json(&filters);
+        assert_eq!(
+            query["query"]["filters"]["trade_filters"]["filters"]["indexed"]["option"],
+            "3days"
+        );
+    }
+
+    #[test]
+    fn test_account_to_query() {
+        let mut filters = TradeFilters::new();
+
+        let query = query_json(&filters);
+        assert!(query["query"]["filters"]["trade_filters"]["filters"]
+            .get("account")
+            .is_none());
+
+        filters.account = Some(TextFilter {
+            text: "OverEpix#3364".to_string(),
+            enabled: true,
+        });
+        let query = query_json(&filters);
+        assert_eq!(
+            query["query"]["filters"]["trade_filters"]["filters"]["account"]["input"],
+            "OverEpix#3364"
+        );
+    }
+
+    #[test]
+    fn test_every_trade_filter_is_mapped() {
+        let range = |min: f64| {
+            Some(RangeFilter {
+                min: Some(min),
+                max: None,
+                enabled: true,
+            })
+        };
+        let text = |text: &str| {
+            Some(TextFilter {
+                text: text.to_string(),
+                enabled: true,
+            })
+        };
+        let stat = |id: &str| StatFilter {
+            id: id.to_string(),
+            text: String::new(),
+            value: StatValue {
+                min: Some(1.0),
+                max: None,
+            },
+            enabled: true,
+            weight: None,
+            option: None,
+        };
+
+        // Every field set and enabled, so any field without a mapping shows up below
+        let mut filters = TradeFilters::new();
+        filters.item_category = text("weapon.crossbow");
+        filters.item_name = text("Rampart Raptor");
+        filters.item_base_type = text("Bombard Crossbow");
+        filters.rarity = text("rare");
+        filters.item_level = range(80.0);
+        filters.physical_dps = range(100.0);
+        filters.elemental_dps = range(50.0);
+        filters.total_dps = range(150.0);
+        filters.attack_speed = range(1.5);
+        filters.critical_chance = range(5.0);
+        filters.reload_time = Some(RangeFilter {
+            min: None,
+            max: Some(0.75),
+            enabled: true,
+        });
+        filters.socket_count = range(2.0);
+        filters.armour = range(100.0);
+        filters.energy_shield = range(50.0);
+        filters.evasion = range(100.0);
+        filters.spirit = range(30.0);
+        filters.block_chance = range(20.0);
+        filters.waystone_drop_chance = range(10.0);
+        filters.explicit_mods.push(stat("explicit.stat_803737631"));
+        filters.implicit_mods.push(stat("implicit.stat_1980802737"));
+        filters.rune_mods.push(stat("rune.stat_3523867985"));
+        filters.stat_groups.push(StatGroup {
+            group_type: StatGroupType::Not,
+            filters: vec![stat("explicit.stat_669069897")],
+            value: StatValue {
+                min: None,
+                max: None,
+            },
+            enabled: true,
+        });
+        filters.price.option = "exalted".to_string();
+        filters.sale_type = OptionFilter {
+            option: SaleType::Priced,
+        };
+        filters.listed_within = OptionFilter {
+            option: ListedWithin::OneDay,
+        };
+        filters.account = text("OverEpix#3364");
+        filters.sort = SortFilter {
+            key: SortKey::Dps,
+            stat_id: None,
+            direction: SortDirection::Desc,
+        };
+        filters.realm = Some(TextFilter {
+            text: "xbox".to_string(),
+            enabled: true,
+        });
+        filters.unique_candidates = vec!["Rampart Raptor".to_string()];
+
+        // Where each TradeFilters field ends up in the serialized query, or None if it
+        // only drives the UI and is never sent to the trade site
+        let mapping: &[(&str, Option<&str>)] = &[
+            ("item_category", Some("/query/filters/type_filters/filters/category/option")),
+            ("item_name", Some("/query/name")),
+            ("item_base_type", Some("/query/type")),
+            ("rarity", Some("/query/filters/type_filters/filters/rarity/option")),
+            ("item_level", Some("/query/filters/misc_filters/filters/ilvl/min")),
+            ("physical_dps", Some("/query/filters/equipment_filters/filters/pdps/min")),
+            ("elemental_dps", Some("/query/filters/equipment_filters/filters/edps/min")),
+            ("total_dps", Some("/query/filters/equipment_filters/filters/dps/min")),
+            ("attack_speed", Some("/query/filters/equipment_filters/filters/aps/min")),
+            ("critical_chance", Some("/query/filters/equipment_filters/filters/crit/min")),
+            ("reload_time", Some("/query/filters/equipment_filters/filters/reload_time/max")),
+            ("socket_count", Some("/query/filters/equipment_filters/filters/rune_sockets/min")),
+            ("armour", Some("/query/filters/equipment_filters/filters/ar/min")),
+            ("energy_shield", Some("/query/filters/equipment_filters/filters/es/min")),
+            ("evasion", Some("/query/filters/equipment_filters/filters/ev/min")),
+            ("spirit", Some("/query/filters/equipment_filters/filters/spirit/min")),
+            ("block_chance", Some("/query/filters/equipment_filters/filters/block/min")),
+            ("waystone_drop_chance", Some("/query/filters/map_filters/filters/map_bonus/min")),
+            ("explicit_mods", Some("/query/stats/0/filters/0/id")),
+            ("implicit_mods", Some("/query/stats/0/filters/1/id")),
+            ("rune_mods", Some("/query/stats/0/filters/2/id")),
+            ("stat_groups", Some("/query/stats/1/type")),
+            ("price", Some("/query/filters/trade_filters/filters/price/option")),
+            ("status", Some("/query/status/option")),
+            ("sale_type", Some("/query/filters/trade_filters/filters/sale_type/option")),
+            ("listed_within", Some("/query/filters/trade_filters/filters/indexed/option")),
+            ("account", Some("/query/filters/trade_filters/filters/account/input")),
+            ("league", Some("/league")),
+            ("realm", Some("/realm")),
+            ("sort", Some("/sort/dps")),
+            ("unique_candidates", None),
+        ];
+
+        let query = query_json(&filters);
+        let fields = serde_json::to_value(&filters).unwrap();
+        for field in fields.as_object().unwrap().keys() {
+            let pointer = mapping
+                .iter()
+                .find(|(name, _)| name == field)
+                .unwrap_or_else(|| panic!("TradeFilters field `{}` has no query mapping", field))
+                .1;
+            if let Some(pointer) = pointer {
+                assert!(
+                    query.pointer(pointer).is_some_and(|value| !value.is_null()),
+                    "TradeFilters field `{}` is not sent as {} in {}",
+                    field,
+                    pointer,
+                    query
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rarity_in_type_filters() {
+        let mut filters = TradeFilters::new();
+        filters.rarity = Some(TextFilter {
+            text: "Unique".to_string(),
+            enabled: true,
+        });
+
+        // The trade site only reads rarity from type_filters, under equipment_filters it's ignored
+        let query = query_json(&filters);
+        let query_filters = &query["query"]["filters"];
+        assert_eq!(query_filters["type_filters"]["filters"]["rarity"]["option"], "unique");
+        assert!(query_filters["equipment_filters"]["filters"].get("rarity").is_none());
+
+        let imported = TradeQuery::from_search_json(query, None, "Standard".to_string())
+            .unwrap()
+            .to_trade_filters(|_| None);
+        assert_eq!(imported.rarity.unwrap().text, "unique");
+    }
+
+    #[test]
+    fn test_filter_groups_merge() {
+        let mut filters = TradeFilters::new();
+        filters.item_category = Some(TextFilter {
+            text: "accessory.ring".to_string(),
+            enabled: true,
+        });
+        filters.rarity = Some(TextFilter {
+            text: "rare".to_string(),
+            enabled: true,
+        });
+        filters.item_level = Some(RangeFilter {
+            min: Some(75.0),
+            max: None,
+            enabled: true,
+        });
+        filters.listed_within = OptionFilter {
+            option: ListedWithin::OneWeek,
+        };
+        filters.price.option = "exalted".to_string();
+        filters.price.max = Some(10.0);
+
+        let query = query_json(&filters);
+        let query_filters = &query["query"]["filters"];
+
+        // Filters that share a group end up side by side instead of replacing each other
+        let type_filters = &query_filters["type_filters"]["filters"];
+        assert_eq!(type_filters["category"]["option"], "accessory.ring");
+        assert_eq!(type_filters["rarity"]["option"], "rare");
+        assert_json_float_eq(&query_filters["misc_filters"]["filters"]["ilvl"]["min"], 75.0);
+
+        let trade_filters = &query_filters["trade_filters"]["filters"];
+        assert_eq!(trade_filters["collapse"]["option"], true);
+        assert_eq!(trade_filters["indexed"]["option"], "1week");
+        assert_eq!(trade_filters["price"]["option"], "exalted");
+        assert_json_float_eq(&trade_filters["price"]["max"], 10.0);
+
+        // Groups without any set filters are left out
+        assert!(query_filters.get("equipment_filters").is_none());
+        assert!(query_filters.get("map_filters").is_none());
+    }
+
+    #[test]
+    fn test_import_keeps_stat_options_and_reports_unknown_values() {
+        let json = serde_json::json!({
+            "query": {
+                "stats": [{
+                    "type": "and",
+                    "filters": [{ "id": "explicit.stat_2954116742", "value": { "option": 12345 } }]
+                }],
+                "filters": {
+                    "equipment_filters": {
+                        "filters": { "ar": { "min": 500 }, "ar_base": { "min": 300 } }
+                    },
+                    "trade_filters": {
+                        "filters": {
+                            "indexed": { "option": "6hours" },
+                            "sale_type": { "option": "priced" }
+                        }
+                    }
+                }
+            }
+        });
+
+        let query = TradeQuery::from_search_json(json, None, "Standard".to_string())
+            .expect("Unknown values shouldn't fail the import");
+        assert_eq!(
+            query.dropped_filters(),
+            vec!["equipment_filters.ar_base", "trade_filters.indexed"]
+        );
+
+        // The stat option survives a round trip back into a query
+        let filters = query.to_trade_filters(|_| None);
+        assert_eq!(filters.listed_within.option, ListedWithin::Any);
+        assert_eq!(filters.sale_type.option, SaleType::Priced);
+        let query = query_json(&filters);
+        assert_eq!(query["query"]["stats"][0]["filters"][0]["value"]["option"], 12345);
+
+        // Periods the site offers beyond the ones in our own dropdown still import
+        let json = serde_json::json!({
+            "query": { "filters": { "trade_filters": { "filters": { "indexed": { "option": "3hours" } } } } }
+        });
+        let query = TradeQuery::from_search_json(json, None, "Standard".to_string()).unwrap();
+        assert!(query.dropped_filters().is_empty());
+        assert_eq!(query.to_trade_filters(|_| None).listed_within.option, ListedWithin::ThreeHours);
+    }
+
+    #[test]
+    fn test_search_json_to_trade_filters() {
+        // As shared from the trade site, including groups and fields we don't model
+        let json = serde_json::json!({
+            "query": {
+                "status": { "option": "securable" },
+                "type": "Gold Ring",
+                "stats": [
+                    {
+                        "type": "and",
+                        "filters": [
+                            { "id": "explicit.stat_803737631", "value": { "min": 100 }, "disabled": false },
+                            { "id": "implicit.stat_1980802737", "value": { "option": true }, "disabled": true },
+                            { "id": "explicit.stat_9999", "disabled": false }
+                        ]
+                    },
+                    {
+                        "type": "count",
+                        "filters": [{ "id": "explicit.stat_210067635", "disabled": false }],
+                        "value": { "min": 1 },
+                        "disabled": false
+                    }
+                ],
+                "filters": {
+                    "type_filters": {
+                        "filters": {
+                            "category": { "option": "accessory.ring" },
+                            "rarity": { "option": "rare" }
+                        }
+                    },
+                    "misc_filters": {
+                        "filters": { "ilvl": { "min": 75 } },
+                        "disabled": true
+                    },
+                    "req_filters": {
+                        "filters": { "lvl": { "max": 60 } }
+                    },
+                    "trade_filters": {
+                        "filters": {
+                            "price": { "option": "exalted", "max": 10 },
+                            "indexed": { "option": "1day" }
+                        }
+                    }
+                }
+            },
+            "sort": { "stat.explicit.stat_803737631": "desc" }
+        });
+
+        let query = TradeQuery::from_search_json(json, None, "Standard".to_string())
+            .expect("Should parse trade site query");
+        assert_eq!(query.dropped_filters(), vec!["req_filters"]);
+        let filters = query.to_trade_filters(|trade_stat| match trade_stat {
+            "explicit.stat_803737631" => Some("# to Accuracy Rating".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(filters.status.option, ListingStatus::Securable);
+        assert_eq!(filters.item_name, None);
+        assert_eq!(filters.item_base_type.unwrap().text, "Gold Ring");
+        assert_eq!(filters.item_category.unwrap().text, "accessory.ring");
+        assert_eq!(filters.rarity.unwrap().text, "rare");
+        assert_eq!(filters.league.unwrap().text, "Standard");
+
+        // Mods are split by their stat prefix and named through the stat lookup
+        assert_eq!(filters.explicit_mods.len(), 2);
+        assert_eq!(filters.explicit_mods[0].text, "# to Accuracy Rating");
+        assert_eq!(filters.explicit_mods[0].value.min, Some(100.0));
+        assert!(filters.explicit_mods[0].enabled);
+        assert_eq!(filters.explicit_mods[1].text, "explicit.stat_9999");
+        assert_eq!(filters.implicit_mods.len(), 1);
+        assert!(!filters.implicit_mods[0].enabled);
+        assert_eq!(filters.implicit_mods[0].option, Some(serde_json::json!(true)));
+
+        assert_eq!(filters.stat_groups.len(), 1);
+        assert_eq!(filters.stat_groups[0].group_type, StatGroupType::Count);
+        assert_eq!(filters.stat_groups[0].value.min, Some(1.0));
+
+        // Filters of a disabled group come back disabled
+        assert_eq!(
+            filters.item_level,
+            Some(RangeFilter {
+                min: Some(75.0),
+                max: None,
+                enabled: false,
+            })
+        );
+
+        assert_eq!(filters.price.option, "exalted");
+        assert_eq!(filters.price.max, Some(10.0));
+        assert_eq!(filters.listed_within.option, ListedWithin::OneDay);
+        assert_eq!(filters.sort.key, SortKey::Stat);
+        assert_eq!(filters.sort.stat_id.as_deref(), Some("explicit.stat_803737631"));
+        assert_eq!(filters.sort.direction, SortDirection::Desc);
+    }
 }
+
@@ -1,6 +1,18 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+use super::exchange::ExchangeResult;
+use super::price_summary::PriceSummary;
+
+/// What a price check finds, listings for single items or offers for bulk exchange items
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PriceCheckResult {
+    Items(TradeResult),
+    Exchange(ExchangeResult),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct TradeResult {
     pub result: Vec<ItemListing>,
@@ -10,9 +22,18 @@ pub struct TradeResult {
     pub current_page: usize,
     #[serde(default)]
     pub total_pages: usize,
+    // Id of the search on the trade site, for opening it there
+    #[serde(default)]
+    pub query_id: String,
+    /// Listings from blocked sellers left out of `total`, among the ones fetched so far
+    #[serde(default)]
+    pub hidden: usize,
+    /// Summary of the prices of the search's first listings, not only this page
+    #[serde(default, skip_deserializing)]
+    pub summary: Option<PriceSummary>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ItemListing {
     pub id: String,
@@ -20,7 +41,7 @@ pub struct ItemListing {
     pub item: ItemInfo,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ItemInfo {
     pub realm: String,
@@ -57,7 +78,7 @@ pub struct ItemInfo {
     pub extended: ExtendedInfo,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ExtendedInfo {
     Full(ItemExtendedInfo),
@@ -70,7 +91,7 @@ impl Default for ExtendedInfo {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct SocketInfo {
     pub group: i32,
@@ -79,7 +100,7 @@ pub struct SocketInfo {
     pub item: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct SocketedItem {
     pub realm: String,
@@ -108,7 +129,7 @@ pub struct SocketedItem {
     pub socket: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ItemProperty {
     pub name: String,
@@ -119,7 +140,7 @@ pub struct ItemProperty {
     pub property_type: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ItemRequirement {
     pub name: String,
@@ -130,7 +151,7 @@ pub struct ItemRequirement {
     pub requirement_type: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ItemExtendedInfo {
     pub dps: Option<f64>,
@@ -144,13 +165,13 @@ pub struct ItemExtendedInfo {
     pub hashes: Option<HashInfo>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ModInfo {
     pub explicit: Option<Vec<ModDetail>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ModDetail {
     pub name: String,
@@ -159,7 +180,7 @@ pub struct ModDetail {
     pub magnitudes: Vec<Magnitude>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct Magnitude {
     pub hash: String,
@@ -167,24 +188,27 @@ pub struct Magnitude {
     pub max: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct HashInfo {
     pub explicit: Option<Vec<(String, Vec<i32>)>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ListingInfo {
     pub method: String,
-    pub indexed: String,
+    pub indexed: DateTime<Utc>,
     pub whisper: Option<String>,
     pub account: AccountInfo,
     pub price: Option<PriceInfo>,
     pub stash: Option<StashInfo>,
+    /// The price converted into the currency the user compares prices in
+    #[serde(default, skip_deserializing)]
+    pub normalized_price: Option<PriceInfo>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct AccountInfo {
     pub name: String,
@@ -193,13 +217,13 @@ pub struct AccountInfo {
     pub realm: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct OnlineInfo {
     pub league: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct PriceInfo {
     #[serde(rename = "type")]
@@ -208,7 +232,7 @@ pub struct PriceInfo {
     pub currency: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct StashInfo {
     pub name: String,
@@ -219,6 +243,7 @@ pub struct StashInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
 
     #[test]
     fn test_deserialize_trade_result() {
@@ -517,7 +542,10 @@ mod tests {
             "c1b3f708d968f0fa2c14a4d1cecb4f691fdd6f0bae623d3c9b10cc27d2480545"
         );
         assert_eq!(item.listing.method, "psapi");
-        assert_eq!(item.listing.indexed, "2025-02-07T02:26:42Z");
+        assert_eq!(
+            item.listing.indexed,
+            Utc.with_ymd_and_hms(2025, 2, 7, 2, 26, 42).unwrap()
+        );
 
         // Verify stash info
         let stash = item.listing.stash.as_ref().unwrap();
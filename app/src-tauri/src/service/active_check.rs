@@ -0,0 +1,99 @@
+use std::future::Future;
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::TradeError;
+
+/// The price check the user is looking at. Starting a new one cancels the requests the
+/// previous one still has in flight, so a slow old search can't overwrite newer results.
+#[derive(Debug, Default)]
+pub struct ActiveCheck {
+    current: Mutex<Check>,
+}
+
+#[derive(Debug, Default)]
+struct Check {
+    token: CancellationToken,
+    // Filters the check last searched for, None until it searches
+    filters: Option<String>,
+}
+
+impl ActiveCheck {
+    /// Cancels the current check and returns the token of the one replacing it
+    pub fn restart(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Ok(mut current) = self.current.lock() {
+            current.replace(token.clone(), None);
+        }
+        token
+    }
+
+    /// Token for a search with the given filters. Another page of the same search keeps the
+    /// current token so its prefetch carries on, changed filters cancel the check and restart it.
+    pub fn search(&self, filters: &str) -> CancellationToken {
+        let Ok(mut current) = self.current.lock() else {
+            return CancellationToken::new();
+        };
+        if current.filters.as_deref() == Some(filters) && !current.token.is_cancelled() {
+            return current.token.clone();
+        }
+        let token = CancellationToken::new();
+        current.replace(token.clone(), Some(filters.to_string()));
+        token
+    }
+}
+
+impl Check {
+    fn replace(&mut self, token: CancellationToken, filters: Option<String>) {
+        self.token.cancel();
+        self.token = token;
+        self.filters = filters;
+    }
+}
+
+/// Runs the future until it finishes or the token is cancelled, dropping it in the latter case
+pub async fn cancellable<T>(
+    token: &CancellationToken,
+    future: impl Future<Output = Result<T, TradeError>>,
+) -> Result<T, TradeError> {
+    tokio::select! {
+        _ = token.cancelled() => Err(TradeError::Cancelled),
+        result = future => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_restart_cancels_previous_check() {
+        let check = ActiveCheck::default();
+        let first = check.restart();
+        let second = check.restart();
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+
+        let pending = cancellable(&first, std::future::pending::<Result<(), TradeError>>()).await;
+        assert_eq!(pending, Err(TradeError::Cancelled));
+        assert_eq!(cancellable(&second, async { Ok(1) }).await, Ok(1));
+    }
+
+    #[test]
+    fn test_paging_keeps_the_check() {
+        let check = ActiveCheck::default();
+        let first_page = check.search("{\"league\": \"Standard\"}");
+        let next_page = check.search("{\"league\": \"Standard\"}");
+        assert!(!first_page.is_cancelled());
+        assert!(!next_page.is_cancelled());
+
+        let changed = check.search("{\"league\": \"Hardcore\"}");
+        assert!(first_page.is_cancelled());
+        assert!(!changed.is_cancelled());
+
+        // A new hotkey press starts over even for the same filters
+        check.restart();
+        assert!(changed.is_cancelled());
+        assert!(!check.search("{\"league\": \"Hardcore\"}").is_cancelled());
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use crate::service::storage;
+
+const BLOCKLIST_FILE_NAME: &str = "blocked_sellers.json";
+
+/// Seller accounts whose listings are hidden from search results
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Blocklist {
+    accounts: BTreeSet<String>,
+}
+
+impl Blocklist {
+    pub fn load() -> Result<Self, String> {
+        Ok(storage::read_json(&blocklist_path()?)?.unwrap_or_default())
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        storage::write_json(&blocklist_path()?, self)
+    }
+
+    /// Returns false if the account was already blocked
+    pub fn block(&mut self, account: &str) -> bool {
+        self.accounts.insert(account.to_string())
+    }
+
+    /// Returns false if the account wasn't blocked
+    pub fn unblock(&mut self, account: &str) -> bool {
+        self.accounts.remove(account)
+    }
+
+    pub fn is_blocked(&self, account: &str) -> bool {
+        self.accounts.contains(account)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    pub fn accounts(&self) -> Vec<String> {
+        self.accounts.iter().cloned().collect()
+    }
+}
+
+fn blocklist_path() -> Result<PathBuf, String> {
+    Ok(storage::config_dir()?.join(BLOCKLIST_FILE_NAME))
+}
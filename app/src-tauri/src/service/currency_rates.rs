@@ -0,0 +1,398 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::TradeError;
+use crate::model::exchange::ExchangeQuery;
+use crate::model::trade_filter::{ListingStatus, SortDirection};
+use crate::model::trade_result::{ItemListing, PriceInfo};
+use crate::service::search_cache::CachedSearch;
+use crate::service::trade_api;
+use crate::service::trade_client::TradeClient;
+
+// Every rate is quoted against this currency, the one most exchange offers are priced in
+const PIVOT_CURRENCY: &str = "exalted";
+
+// Rates drift slowly, and the exchange has the strictest rate limit of the trade API
+const RATE_TTL: Duration = Duration::from_secs(30 * 60);
+
+// How long a search waits for rates it has none of yet, converting nothing would make the
+// first search in a league look unsorted
+const FIRST_RATE_WAIT: Duration = Duration::from_secs(3);
+
+// The cheapest few offers, so a single mispriced one doesn't set the rate
+const QUOTED_OFFERS: usize = 5;
+
+/// Worth of currencies in the pivot currency, for comparing prices listed in different ones
+#[derive(Debug, Default)]
+pub struct CurrencyRates {
+    rates: HashMap<String, f64>,
+}
+
+impl CurrencyRates {
+    fn value(&self, currency: &str) -> Option<f64> {
+        if currency == PIVOT_CURRENCY {
+            return Some(1.0);
+        }
+        self.rates.get(currency).copied()
+    }
+
+    /// The price in `base` currency, None if either currency has no known rate
+    pub fn convert(&self, price: &PriceInfo, base: &str) -> Option<PriceInfo> {
+        let amount = if price.currency == base {
+            price.amount
+        } else {
+            price.amount * self.value(&price.currency)? / self.value(base)?
+        };
+        Some(PriceInfo {
+            price_type: price.price_type.clone(),
+            amount,
+            currency: base.to_string(),
+        })
+    }
+
+    /// Converts the listings' prices into `base` currency, keeping their order
+    pub fn normalize(&self, listings: &mut [ItemListing], base: &str) {
+        for listing in listings.iter_mut() {
+            listing.listing.normalized_price = listing
+                .listing
+                .price
+                .as_ref()
+                .and_then(|price| self.convert(price, base));
+        }
+    }
+}
+
+/// Sorts normalized listings by their converted price in `direction`.
+/// Listings that couldn't be converted keep their order after the others.
+fn sort_by_normalized_price(listings: &mut [ItemListing], direction: SortDirection) {
+    listings.sort_by(|a, b| {
+        match (&a.listing.normalized_price, &b.listing.normalized_price) {
+            (Some(a), Some(b)) => match direction {
+                SortDirection::Asc => a.amount.total_cmp(&b.amount),
+                SortDirection::Desc => b.amount.total_cmp(&a.amount),
+            },
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+    });
+}
+
+/// The search's result ids with the first `count` sorted by their price in `base` currency, the
+/// rest keep the trade site's order. Ids among the first `count` that aren't fetched are left out.
+pub fn order_by_normalized_price(
+    search: &CachedSearch,
+    count: usize,
+    rates: &CurrencyRates,
+    base: &str,
+    direction: SortDirection,
+) -> Vec<String> {
+    let ids = search.result_ids.iter().take(count).map(String::as_str).collect::<Vec<_>>();
+    let mut listings = search.listings(&ids).into_iter().flatten().collect::<Vec<_>>();
+    rates.normalize(&mut listings, base);
+    sort_by_normalized_price(&mut listings, direction);
+
+    listings
+        .into_iter()
+        .map(|listing| listing.id)
+        .chain(search.result_ids.iter().skip(count).cloned())
+        .collect()
+}
+
+// Realm and id of a league, leagues in different realms share ids like "Standard"
+type LeagueKey = (Option<String>, String);
+
+fn league_key(realm: Option<&str>, league: &str) -> LeagueKey {
+    (realm.map(str::to_string), league.to_string())
+}
+
+#[derive(Debug)]
+struct CachedRate {
+    // None when the exchange had no offers, so the currency isn't asked for on every search
+    rate: Option<f64>,
+    fetched_at: Instant,
+}
+
+/// Currency rates from the bulk exchange, cached per league and fetched only for the
+/// currencies a search needs
+#[derive(Debug, Default, Clone)]
+pub struct CurrencyRateCache {
+    leagues: Arc<Mutex<HashMap<LeagueKey, HashMap<String, CachedRate>>>>,
+    // Currencies per league a refresh is already fetching, so searches don't queue them twice
+    refreshing: Arc<Mutex<HashSet<(LeagueKey, String)>>>,
+}
+
+impl CurrencyRateCache {
+    /// Rates of the currencies, stale ones are used while they refresh in the background.
+    /// Currencies that have no rate yet are waited for up to `FIRST_RATE_WAIT`, a refresh still
+    /// running by then carries on for later searches. Currencies that fail are left out.
+    pub async fn rates<'a>(
+        &self,
+        client: &Arc<TradeClient>,
+        realm: Option<&str>,
+        league: &str,
+        currencies: impl IntoIterator<Item = &'a str>,
+    ) -> Result<CurrencyRates, String> {
+        let mut currencies = currencies
+            .into_iter()
+            .filter(|currency| *currency != PIVOT_CURRENCY)
+            .collect::<Vec<_>>();
+        currencies.sort_unstable();
+        currencies.dedup();
+
+        let key = league_key(realm, league);
+        let rates = self.known(&key, &currencies)?;
+        let missing = self.claim_missing(&key, &currencies)?;
+        if missing.is_empty() {
+            return Ok(rates);
+        }
+
+        let first_use = missing.iter().any(|currency| !rates.rates.contains_key(currency));
+        let refresh = tokio::spawn({
+            let cache = self.clone();
+            let client = client.clone();
+            let realm = realm.map(str::to_string);
+            let league = league.to_string();
+            async move { cache.refresh(&client, realm.as_deref(), &league, missing).await }
+        });
+        if !first_use {
+            return Ok(rates);
+        }
+        if tokio::time::timeout(FIRST_RATE_WAIT, refresh).await.is_err() {
+            log::info!("Currency rates in {} are still loading, converting with what's known", league);
+        }
+        self.known(&key, &currencies)
+    }
+
+    // Cached rates of the currencies, stale ones included
+    fn known(&self, key: &LeagueKey, currencies: &[&str]) -> Result<CurrencyRates, String> {
+        let leagues = self
+            .leagues
+            .lock()
+            .map_err(|e| format!("Failed to read currency rates: {}", e))?;
+        let cached = leagues.get(key);
+        Ok(CurrencyRates {
+            rates: currencies
+                .iter()
+                .filter_map(|currency| Some((currency.to_string(), cached?.get(*currency)?.rate?)))
+                .collect(),
+        })
+    }
+
+    // Currencies whose rate is missing or stale and that no refresh is fetching yet. They count
+    // as being fetched until `refresh` is done with them.
+    fn claim_missing(&self, key: &LeagueKey, currencies: &[&str]) -> Result<Vec<String>, String> {
+        let leagues = self
+            .leagues
+            .lock()
+            .map_err(|e| format!("Failed to read currency rates: {}", e))?;
+        let mut refreshing = self
+            .refreshing
+            .lock()
+            .map_err(|e| format!("Failed to read currency rates: {}", e))?;
+        let cached = leagues.get(key);
+
+        Ok(currencies
+            .iter()
+            .filter(|currency| {
+                !cached
+                    .and_then(|rates| rates.get(**currency))
+                    .is_some_and(|rate| rate.fetched_at.elapsed() < RATE_TTL)
+            })
+            .filter(|currency| refreshing.insert((key.clone(), currency.to_string())))
+            .map(|currency| currency.to_string())
+            .collect())
+    }
+
+    // Fetches the claimed currencies. Each rate is kept as soon as it arrives, a failed
+    // currency is only logged and asked for again by a later search.
+    async fn refresh(&self, client: &TradeClient, realm: Option<&str>, league: &str, currencies: Vec<String>) {
+        let key = league_key(realm, league);
+        // One currency per request, offers for several at once would crowd each other out
+        for currency in currencies {
+            match fetch_rate(client, realm, league, &currency).await {
+                Ok(rate) => {
+                    log::info!("Rate of {} in {}: {:?}", currency, league, rate);
+                    if let Err(e) = self.store(&key, &currency, rate) {
+                        log::warn!("{}", e);
+                    }
+                }
+                Err(e) => log::warn!("Failed to fetch the rate of {} in {}: {}", currency, league, e),
+            }
+            if let Ok(mut refreshing) = self.refreshing.lock() {
+                refreshing.remove(&(key.clone(), currency));
+            }
+        }
+    }
+
+    fn store(&self, key: &LeagueKey, currency: &str, rate: Option<f64>) -> Result<(), String> {
+        let mut leagues = self
+            .leagues
+            .lock()
+            .map_err(|e| format!("Failed to update currency rates: {}", e))?;
+        leagues.entry(key.clone()).or_default().insert(
+            currency.to_string(),
+            CachedRate {
+                rate,
+                fetched_at: Instant::now(),
+            },
+        );
+        Ok(())
+    }
+}
+
+// Worth of one `currency` in the pivot currency, from what sellers on the exchange ask for it
+async fn fetch_rate(client: &TradeClient, realm: Option<&str>, league: &str, currency: &str) -> Result<Option<f64>, TradeError> {
+    let query = ExchangeQuery::new(
+        realm.map(str::to_string),
+        league.to_string(),
+        ListingStatus::Online,
+        vec![PIVOT_CURRENCY.to_string()],
+        vec![currency.to_string()],
+    );
+    match trade_api::exchange(client, &query).await {
+        Ok(result) => Ok(quote(result.offers.iter().map(|offer| offer.ratio))),
+        Err(TradeError::NoResults) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Median of the cheapest ratios, which arrive sorted cheapest first
+fn quote(ratios: impl Iterator<Item = f64>) -> Option<f64> {
+    let ratios = ratios.take(QUOTED_OFFERS).collect::<Vec<_>>();
+    if ratios.is_empty() {
+        return None;
+    }
+    let middle = ratios.len() / 2;
+    if ratios.len() % 2 == 0 {
+        Some((ratios[middle - 1] + ratios[middle]) / 2.0)
+    } else {
+        Some(ratios[middle])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(amount: f64, currency: &str) -> PriceInfo {
+        PriceInfo {
+            price_type: "~price".to_string(),
+            amount,
+            currency: currency.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_quote_ignores_outliers() {
+        assert_eq!(quote([0.01, 150.0, 151.0, 152.0, 160.0, 900.0].into_iter()), Some(151.0));
+        assert_eq!(quote([10.0, 20.0].into_iter()), Some(15.0));
+        assert_eq!(quote(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_convert_between_currencies() {
+        let rates = CurrencyRates {
+            rates: HashMap::from([("divine".to_string(), 200.0), ("chaos".to_string(), 10.0)]),
+        };
+
+        assert_eq!(rates.convert(&price(2.0, "divine"), "exalted").unwrap().amount, 400.0);
+        assert_eq!(rates.convert(&price(50.0, "exalted"), "chaos").unwrap().amount, 5.0);
+        assert_eq!(rates.convert(&price(40.0, "chaos"), "divine").unwrap().amount, 2.0);
+        assert_eq!(rates.convert(&price(3.0, "regal"), "regal").unwrap().amount, 3.0);
+        assert!(rates.convert(&price(3.0, "regal"), "exalted").is_none());
+    }
+
+    fn listing(id: &str, amount: f64, currency: &str) -> ItemListing {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "listing": {
+                "method": "psapi",
+                "indexed": "2025-02-07T02:26:42Z",
+                "account": {"name": "Seller#1"},
+                "price": {"type": "~price", "amount": amount, "currency": currency}
+            },
+            "item": {
+                "realm": "poe2",
+                "verified": true,
+                "w": 1,
+                "h": 1,
+                "icon": "",
+                "league": "Standard",
+                "id": id,
+                "name": "",
+                "typeLine": "Gold Ring",
+                "baseType": "Gold Ring",
+                "frameType": 2
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_normalize_keeps_order_until_sorted() {
+        let rates = CurrencyRates {
+            rates: HashMap::from([("divine".to_string(), 200.0)]),
+        };
+        let mut listings = vec![
+            listing("a", 300.0, "exalted"),
+            listing("b", 3.0, "regal"),
+            listing("c", 1.0, "divine"),
+        ];
+        let ids = |listings: &[ItemListing]| listings.iter().map(|l| l.id.clone()).collect::<Vec<_>>();
+
+        rates.normalize(&mut listings, "exalted");
+        assert_eq!(ids(&listings), vec!["a", "b", "c"]);
+        assert_eq!(listings[2].listing.normalized_price.as_ref().unwrap().amount, 200.0);
+        assert!(listings[1].listing.normalized_price.is_none());
+
+        sort_by_normalized_price(&mut listings, SortDirection::Asc);
+        assert_eq!(ids(&listings), vec!["c", "a", "b"]);
+        sort_by_normalized_price(&mut listings, SortDirection::Desc);
+        assert_eq!(ids(&listings), vec!["a", "c", "b"]);
+    }
+
+    #[test]
+    fn test_missing_rates_are_claimed_once() {
+        let cache = CurrencyRateCache::default();
+        let key = league_key(None, "Standard");
+        cache.store(&key, "divine", Some(200.0)).unwrap();
+
+        let rates = cache.known(&key, &["divine", "chaos"]).unwrap();
+        assert_eq!(rates.value("divine"), Some(200.0));
+        assert_eq!(rates.value("chaos"), None);
+        assert_eq!(cache.claim_missing(&key, &["divine", "chaos"]).unwrap(), vec!["chaos"]);
+
+        // Already being refreshed, so a second search doesn't fetch it again
+        assert!(cache.claim_missing(&key, &["chaos"]).unwrap().is_empty());
+        // Other realms keep their own rates
+        let console = league_key(Some("xbox"), "Standard");
+        assert_eq!(cache.known(&console, &["divine"]).unwrap().value("divine"), None);
+        assert_eq!(cache.claim_missing(&console, &["chaos"]).unwrap(), vec!["chaos"]);
+    }
+
+    #[test]
+    fn test_order_whole_sample_by_normalized_price() {
+        let rates = CurrencyRates {
+            rates: HashMap::from([("divine".to_string(), 200.0)]),
+        };
+        let ids = ["a", "b", "c", "d", "e"].map(str::to_string).to_vec();
+        let search = CachedSearch::new("AbC123".to_string(), ids);
+        // "d" isn't fetched, "e" is past the sample and keeps its place
+        search.store_listings(&[
+            listing("a", 300.0, "exalted"),
+            listing("b", 3.0, "regal"),
+            listing("c", 1.0, "divine"),
+        ]);
+
+        assert_eq!(
+            order_by_normalized_price(&search, 4, &rates, "exalted", SortDirection::Asc),
+            vec!["c", "a", "b", "e"]
+        );
+        assert_eq!(
+            order_by_normalized_price(&search, 4, &rates, "exalted", SortDirection::Desc),
+            vec!["a", "c", "b", "e"]
+        );
+    }
+}
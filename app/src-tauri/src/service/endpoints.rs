@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::service::storage;
+
+const ENDPOINTS_FILE_NAME: &str = "endpoints.json";
+
+/// Where the trade API lives. The default targets the official trade site, an `endpoints.json`
+/// in the config directory overrides any of the fields, e.g. to point at a local server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Endpoints {
+    /// Scheme and host without a trailing slash, e.g. `https://www.pathofexile.com`
+    pub base_url: String,
+    /// Path of the trade site under the base URL, `trade2` for Path of Exile 2
+    pub trade_path: String,
+    /// Realm for searches whose league doesn't name one, `poe2` is the PC realm
+    pub realm: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            base_url: "https://www.pathofexile.com".to_string(),
+            trade_path: "trade2".to_string(),
+            realm: "poe2".to_string(),
+        }
+    }
+}
+
+impl Endpoints {
+    /// Reads the endpoints from the config file, the defaults if there is none
+    pub fn load() -> Result<Self, String> {
+        Self::load_from(&endpoints_path()?)
+    }
+
+    fn load_from(path: &Path) -> Result<Self, String> {
+        Ok(storage::read_json(path)?.unwrap_or_default())
+    }
+
+    pub fn data_url(&self, kind: &str) -> String {
+        format!("{}/data/{}", self.api_url(), kind)
+    }
+
+    pub fn search_url(&self, realm: Option<&str>, league: &str) -> String {
+        format!("{}/search/{}/{}", self.api_url(), self.realm(realm), urlencoding::encode(league))
+    }
+
+    pub fn saved_search_url(&self, realm: Option<&str>, league: &str, id: &str) -> String {
+        format!("{}/{}", self.search_url(realm, league), id)
+    }
+
+    pub fn exchange_url(&self, realm: Option<&str>, league: &str) -> String {
+        format!("{}/exchange/{}/{}", self.api_url(), self.realm(realm), urlencoding::encode(league))
+    }
+
+    pub fn fetch_url(&self, ids: &[&str]) -> String {
+        format!("{}/fetch/{}", self.api_url(), ids.join(","))
+    }
+
+    /// Websocket that pushes the ids of new listings matching a search
+    pub fn live_url(&self, realm: Option<&str>, league: &str, id: &str) -> String {
+        // http becomes ws and https becomes wss
+        let base_url = self.base_url.replacen("http", "ws", 1);
+        format!(
+            "{}/api/{}/live/{}/{}/{}",
+            base_url,
+            self.trade_path,
+            self.realm(realm),
+            urlencoding::encode(league),
+            id
+        )
+    }
+
+    /// Link to the search on the trade site itself
+    pub fn website_url(&self, realm: Option<&str>, league: &str, id: &str) -> String {
+        format!(
+            "{}/{}/search/{}/{}/{}",
+            self.base_url,
+            self.trade_path,
+            self.realm(realm),
+            urlencoding::encode(league),
+            id
+        )
+    }
+
+    /// Link to a bulk exchange search on the trade site
+    pub fn exchange_website_url(&self, realm: Option<&str>, league: &str, id: &str) -> String {
+        format!(
+            "{}/{}/exchange/{}/{}/{}",
+            self.base_url,
+            self.trade_path,
+            self.realm(realm),
+            urlencoding::encode(league),
+            id
+        )
+    }
+
+    /// Splits a trade site search link like `https://www.pathofexile.com/trade2/search/poe2/Standard/AbC123`
+    /// into its realm, league and query id. The realm segment is optional.
+    pub fn parse_website_url(&self, url: &str) -> Option<(Option<String>, String, String)> {
+        let path = url.split_once(&format!("/{}/search/", self.trade_path))?.1;
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let segments = path.split('/').filter(|segment| !segment.is_empty()).collect::<Vec<_>>();
+
+        let (realm, league, id) = match segments.as_slice() {
+            [realm, league, id] => (Some(realm.to_string()), league, id),
+            [league, id] if *league != self.realm => (None, league, id),
+            _ => return None,
+        };
+
+        let league = urlencoding::decode(league).ok()?.into_owned();
+        Some((realm, league, id.to_string()))
+    }
+
+    fn api_url(&self) -> String {
+        format!("{}/api/{}", self.base_url, self.trade_path)
+    }
+
+    // Leagues of console realms are searched in their own realm, everything else in ours
+    fn realm<'a>(&'a self, realm: Option<&'a str>) -> &'a str {
+        realm.unwrap_or(&self.realm)
+    }
+}
+
+fn endpoints_path() -> Result<PathBuf, String> {
+    Ok(storage::config_dir()?.join(ENDPOINTS_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_urls() {
+        let endpoints = Endpoints::default();
+        assert_eq!(
+            endpoints.data_url("stats"),
+            "https://www.pathofexile.com/api/trade2/data/stats"
+        );
+        assert_eq!(
+            endpoints.search_url(None, "Dawn of the Hunt"),
+            "https://www.pathofexile.com/api/trade2/search/poe2/Dawn%20of%20the%20Hunt"
+        );
+        assert_eq!(
+            endpoints.exchange_url(None, "Standard"),
+            "https://www.pathofexile.com/api/trade2/exchange/poe2/Standard"
+        );
+        assert_eq!(
+            endpoints.fetch_url(&["a", "b"]),
+            "https://www.pathofexile.com/api/trade2/fetch/a,b"
+        );
+        assert_eq!(
+            endpoints.live_url(None, "Standard", "AbC123"),
+            "wss://www.pathofexile.com/api/trade2/live/poe2/Standard/AbC123"
+        );
+        assert_eq!(
+            endpoints.website_url(None, "Standard", "AbC123"),
+            "https://www.pathofexile.com/trade2/search/poe2/Standard/AbC123"
+        );
+
+        // Console leagues keep their own realm
+        assert_eq!(
+            endpoints.search_url(Some("xbox"), "Standard"),
+            "https://www.pathofexile.com/api/trade2/search/xbox/Standard"
+        );
+        assert_eq!(
+            endpoints.live_url(Some("sony"), "Standard", "AbC123"),
+            "wss://www.pathofexile.com/api/trade2/live/sony/Standard/AbC123"
+        );
+    }
+
+    #[test]
+    fn test_endpoints_from_config_file() {
+        let path = std::env::temp_dir()
+            .join(format!("chiseled-endpoints-{}", std::process::id()))
+            .join(ENDPOINTS_FILE_NAME);
+        assert_eq!(Endpoints::load_from(&path).unwrap(), Endpoints::default());
+
+        // Fields left out of the file keep their defaults
+        storage::write_json(&path, &serde_json::json!({ "base_url": "http://127.0.0.1:8080", "realm": "xbox" }))
+            .expect("Should write config");
+        let endpoints = Endpoints::load_from(&path).unwrap();
+        assert_eq!(endpoints.base_url, "http://127.0.0.1:8080");
+        assert_eq!(endpoints.realm, "xbox");
+        assert_eq!(endpoints.trade_path, "trade2");
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_parse_website_url() {
+        let endpoints = Endpoints::default();
+        assert_eq!(
+            endpoints.parse_website_url("https://www.pathofexile.com/trade2/search/poe2/Standard/AbC123"),
+            Some((Some("poe2".to_string()), "Standard".to_string(), "AbC123".to_string()))
+        );
+        assert_eq!(
+            endpoints.parse_website_url("https://www.pathofexile.com/trade2/search/poe2/Dawn%20of%20the%20Hunt/AbC123?q=1"),
+            Some((Some("poe2".to_string()), "Dawn of the Hunt".to_string(), "AbC123".to_string()))
+        );
+        assert_eq!(
+            endpoints.parse_website_url("https://www.pathofexile.com/trade2/search/Standard/AbC123"),
+            Some((None, "Standard".to_string(), "AbC123".to_string()))
+        );
+        assert_eq!(
+            endpoints.parse_website_url("https://www.pathofexile.com/trade2/search/poe2/Standard"),
+            None
+        );
+        assert_eq!(endpoints.parse_website_url("{\"query\": {}}"), None);
+    }
+}
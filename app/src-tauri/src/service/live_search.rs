@@ -0,0 +1,254 @@
+use futures_util::StreamExt;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::{self, Message};
+
+use crate::error::TradeError;
+use crate::model::trade_result::ItemListing;
+use crate::service::trade_api::{self, PAGE_SIZE};
+use crate::service::trade_client::TradeClient;
+
+// The trade site only lets an account keep this many live searches open
+const MAX_LIVE_SEARCHES: usize = 20;
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+// A connection that stayed up this long was healthy, so the next drop retries quickly again
+const STABLE_CONNECTION: Duration = Duration::from_secs(30);
+
+/// What happened to a live search, passed to the callback given when it was started
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LiveSearchEvent {
+    Connected {
+        query_id: String,
+    },
+    Listing {
+        query_id: String,
+        listing: Box<ItemListing>,
+    },
+    Reconnecting {
+        query_id: String,
+        retry_in: u64,
+        error: TradeError,
+    },
+    /// The search won't reconnect, e.g. because it expired or we aren't logged in
+    Stopped {
+        query_id: String,
+        error: TradeError,
+    },
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LiveMessage {
+    #[serde(default)]
+    new: Vec<String>,
+    auth: Option<bool>,
+}
+
+/// Runs live searches in the background, one websocket per search id
+#[derive(Debug, Default)]
+pub struct LiveSearchManager {
+    searches: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl LiveSearchManager {
+    /// Starts listening for new listings of the search. Starting a search that's already
+    /// running does nothing.
+    pub fn start(
+        &self,
+        client: Arc<TradeClient>,
+        realm: Option<&str>,
+        league: &str,
+        query_id: &str,
+        on_event: impl Fn(LiveSearchEvent) + Send + Sync + 'static,
+    ) -> Result<(), TradeError> {
+        let mut searches = self
+            .searches
+            .lock()
+            .map_err(|e| format!("Failed to read live searches: {}", e))?;
+        searches.retain(|_, task| !task.is_finished());
+
+        if searches.contains_key(query_id) {
+            return Ok(());
+        }
+        if searches.len() >= MAX_LIVE_SEARCHES {
+            return Err(format!("At most {} live searches can run at once", MAX_LIVE_SEARCHES).into());
+        }
+
+        let url = client.endpoints.live_url(realm, league, query_id);
+        let task = tokio::spawn(run(client, url, query_id.to_string(), on_event));
+        searches.insert(query_id.to_string(), task);
+        Ok(())
+    }
+
+    /// Stops the search, returns false if it wasn't running
+    pub fn stop(&self, query_id: &str) -> bool {
+        let Ok(mut searches) = self.searches.lock() else {
+            return false;
+        };
+        let Some(task) = searches.remove(query_id) else {
+            return false;
+        };
+        task.abort();
+        true
+    }
+
+    /// Ids of the searches that are still running
+    pub fn active(&self) -> Vec<String> {
+        match self.searches.lock() {
+            Ok(searches) => searches
+                .iter()
+                .filter(|(_, task)| !task.is_finished())
+                .map(|(query_id, _)| query_id.clone())
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+async fn run(client: Arc<TradeClient>, url: String, query_id: String, on_event: impl Fn(LiveSearchEvent)) {
+    let mut backoff = MIN_BACKOFF;
+    loop {
+        let connected_at = Instant::now();
+        let error = match listen(&client, &url, &query_id, &on_event).await {
+            Ok(()) => TradeError::Network {
+                request: "Live search".to_string(),
+                message: "Connection closed".to_string(),
+            },
+            Err(e) if is_fatal(&e) => {
+                log::warn!("Live search {} stopped: {}", query_id, e);
+                on_event(LiveSearchEvent::Stopped { query_id, error: e });
+                return;
+            }
+            Err(e) => e,
+        };
+
+        if connected_at.elapsed() > STABLE_CONNECTION {
+            backoff = MIN_BACKOFF;
+        }
+        log::info!("Live search {} disconnected, retrying in {}s: {}", query_id, backoff.as_secs(), error);
+        on_event(LiveSearchEvent::Reconnecting {
+            query_id: query_id.clone(),
+            retry_in: backoff.as_secs(),
+            error,
+        });
+
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+// Retrying won't help when the search is gone or the trade site wants a login
+fn is_fatal(error: &TradeError) -> bool {
+    matches!(
+        error,
+        TradeError::NotLoggedIn { .. } | TradeError::InvalidQuery { .. } | TradeError::Api { status: 404, .. }
+    )
+}
+
+/// Connects and passes on new listings until the connection drops
+async fn listen(
+    client: &TradeClient,
+    url: &str,
+    query_id: &str,
+    on_event: &impl Fn(LiveSearchEvent),
+) -> Result<(), TradeError> {
+    let network_error = |e: tungstenite::Error| match e {
+        tungstenite::Error::Http(response) => {
+            let status = StatusCode::from_u16(response.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+            TradeError::from_api(status, None, format!("Live search refused with {}", status))
+        }
+        e => TradeError::Network {
+            request: "Live search".to_string(),
+            message: e.to_string(),
+        },
+    };
+
+    let mut request = url.into_client_request().map_err(network_error)?;
+    let headers = request.headers_mut();
+    // The trade site only accepts websockets opened from its own pages
+    if let Ok(origin) = HeaderValue::from_str(&client.endpoints.base_url) {
+        headers.insert("Origin", origin);
+    }
+    headers.insert("User-Agent", HeaderValue::from_static("chiseled-price-checker"));
+    if let Some(cookies) = client.cookie_header().and_then(|cookies| HeaderValue::from_str(&cookies).ok()) {
+        headers.insert("Cookie", cookies);
+    }
+
+    let (mut stream, _) = tokio_tungstenite::connect_async(request).await.map_err(network_error)?;
+    log::info!("Live search {} connected", query_id);
+    on_event(LiveSearchEvent::Connected {
+        query_id: query_id.to_string(),
+    });
+
+    while let Some(message) = stream.next().await {
+        let text = match message.map_err(network_error)? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            // Pings are answered by tungstenite itself
+            _ => continue,
+        };
+
+        let message = parse_message(&text);
+        if message.auth == Some(false) {
+            return Err(TradeError::NotLoggedIn {
+                status: 401,
+                message: "Live search needs a session id".to_string(),
+            });
+        }
+
+        let ids = message.new.iter().map(String::as_str).collect::<Vec<_>>();
+        for batch in ids.chunks(PAGE_SIZE) {
+            match trade_api::fetch_listings(client, batch).await {
+                Ok(fetched) => {
+                    for listing in fetched.result {
+                        on_event(LiveSearchEvent::Listing {
+                            query_id: query_id.to_string(),
+                            listing: Box::new(listing),
+                        });
+                    }
+                }
+                // One failed fetch shouldn't end the search, later listings may still arrive
+                Err(e) => log::warn!("Failed to fetch live search {} listings: {}", query_id, e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_message(text: &str) -> LiveMessage {
+    serde_json::from_str(text).unwrap_or_else(|e| {
+        log::warn!("Ignoring live search message {}: {}", text, e);
+        LiveMessage::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_live_messages() {
+        let message = parse_message(r#"{"new": ["a", "b"]}"#);
+        assert_eq!(message.new, vec!["a", "b"]);
+        assert_eq!(message.auth, None);
+
+        assert_eq!(parse_message(r#"{"auth": false}"#).auth, Some(false));
+        assert!(parse_message("not json").new.is_empty());
+    }
+
+    #[test]
+    fn test_fatal_errors() {
+        assert!(is_fatal(&TradeError::from_api(StatusCode::NOT_FOUND, None, String::new())));
+        assert!(is_fatal(&TradeError::from_api(StatusCode::UNAUTHORIZED, None, String::new())));
+        assert!(!is_fatal(&TradeError::from_api(StatusCode::BAD_GATEWAY, None, String::new())));
+    }
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::service::trade_client::TradeClient;
+use crate::service::{storage, trade_api};
+
+const CACHE_FILE_NAME: &str = "trade_mappings.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedMappings {
+    /// Unix timestamp (seconds) of when the mappings were fetched from the trade API
+    pub fetched_at: u64,
+    /// SHA-256 of the stats, items and static payloads, used to detect corrupted or changed data
+    pub hash: String,
+    pub stats: Value,
+    pub items: Value,
+    /// Bulk exchange items, missing from caches written by older versions
+    #[serde(default, rename = "static")]
+    pub static_data: Value,
+    /// League ids that were live when the mappings were fetched
+    #[serde(default)]
+    pub leagues: Vec<String>,
+}
+
+impl CachedMappings {
+    pub fn new(stats: Value, items: Value, static_data: Value, leagues: Vec<String>) -> Self {
+        let fetched_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            fetched_at,
+            hash: content_hash(&stats, &items, &static_data),
+            stats,
+            items,
+            static_data,
+            leagues,
+        }
+    }
+
+    pub fn age(&self) -> Duration {
+        let fetched_at = UNIX_EPOCH + Duration::from_secs(self.fetched_at);
+        SystemTime::now()
+            .duration_since(fetched_at)
+            .unwrap_or_default()
+    }
+
+    pub fn load() -> Result<Option<Self>, String> {
+        Self::load_from(&cache_path()?)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&cache_path()?)
+    }
+
+    fn load_from(path: &Path) -> Result<Option<Self>, String> {
+        let Some(cached) = storage::read_json::<Self>(path)? else {
+            return Ok(None);
+        };
+
+        if content_hash(&cached.stats, &cached.items, &cached.static_data) != cached.hash {
+            log::warn!("Ignoring cached trade mappings at {}: hash mismatch", path.display());
+            return Ok(None);
+        }
+
+        Ok(Some(cached))
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), String> {
+        storage::write_json(path, self)
+    }
+}
+
+/// Fetches fresh mappings from the trade API and writes them to the on-disk cache.
+/// A failed write is only logged since the fetched mappings are still usable.
+pub async fn fetch_and_store(client: &TradeClient) -> Result<CachedMappings, String> {
+    let ((stats, items, static_data), leagues) = tokio::try_join!(
+        trade_api::fetch_mappings(client),
+        trade_api::fetch_leagues(client)
+    )
+    .map_err(|e| e.to_string())?;
+    let league_ids = leagues.into_iter().map(|league| league.id).collect();
+    let mappings = CachedMappings::new(stats, items, static_data, league_ids);
+
+    if let Err(e) = mappings.save() {
+        log::warn!("Failed to cache trade mappings: {}", e);
+    }
+
+    Ok(mappings)
+}
+
+fn cache_path() -> Result<PathBuf, String> {
+    Ok(storage::data_dir()?.join(CACHE_FILE_NAME))
+}
+
+fn content_hash(stats: &Value, items: &Value, static_data: &Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(stats.to_string());
+    hasher.update(items.to_string());
+    // Left out when missing, so caches written before it was fetched still match their hash
+    if !static_data.is_null() {
+        hasher.update(static_data.to_string());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cached_mappings_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("chiseled-mapping-cache-{}", std::process::id()))
+            .join(CACHE_FILE_NAME);
+
+        let mappings = CachedMappings::new(
+            json!({ "result": [{ "entries": [{ "id": "explicit.stat_803737631", "text": "# to Accuracy Rating" }] }] }),
+            json!({ "result": [{ "id": "weapon", "label": "Weapons", "entries": [] }] }),
+            json!({ "result": [{ "id": "Currency", "label": "Currency", "entries": [{ "id": "exalted", "text": "Exalted Orb" }] }] }),
+            vec!["Standard".to_string()],
+        );
+        mappings.save_to(&path).expect("Should write cache");
+
+        let loaded = CachedMappings::load_from(&path)
+            .expect("Should read cache")
+            .expect("Cache should exist");
+        assert_eq!(loaded.hash, mappings.hash);
+        assert_eq!(loaded.fetched_at, mappings.fetched_at);
+        assert_eq!(loaded.stats, mappings.stats);
+        assert_eq!(loaded.static_data, mappings.static_data);
+        assert_eq!(loaded.leagues, mappings.leagues);
+
+        // Tampered payloads no longer match the stored hash and are ignored
+        let mut tampered = loaded;
+        tampered.items = json!({ "result": [] });
+        tampered.save_to(&path).expect("Should write cache");
+        assert!(CachedMappings::load_from(&path).unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_cache_without_static_data_still_loads() {
+        let path = std::env::temp_dir()
+            .join(format!("chiseled-mapping-cache-old-{}", std::process::id()))
+            .join(CACHE_FILE_NAME);
+
+        let stats = json!({ "result": [] });
+        let items = json!({ "result": [] });
+        let mut hasher = Sha256::new();
+        hasher.update(stats.to_string());
+        hasher.update(items.to_string());
+        let old = json!({
+            "fetched_at": 0,
+            "hash": format!("{:x}", hasher.finalize()),
+            "stats": stats,
+            "items": items,
+        });
+        storage::write_json(&path, &old).expect("Should write cache");
+
+        let loaded = CachedMappings::load_from(&path)
+            .expect("Should read cache")
+            .expect("Old cache should still match its hash");
+        assert!(loaded.static_data.is_null());
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -1 +1,12 @@
+pub mod active_check;
+pub mod blocklist;
+pub mod currency_rates;
+pub mod endpoints;
+pub mod live_search;
+pub mod mapping_cache;
+pub mod presets;
+pub mod rate_limit;
+pub mod search_cache;
+pub mod storage;
 pub mod trade_api;
+pub mod trade_client;
@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::model::trade_filter::TradeFilters;
+use crate::service::storage;
+
+const PRESETS_FILE_NAME: &str = "presets.json";
+
+// Bump when the file layout changes, and migrate older files in `Presets::from_json`.
+// New TradeFilters fields don't need a bump since missing fields fall back to their defaults.
+const PRESETS_VERSION: u64 = 1;
+
+/// Named TradeFilters saved by the user
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Presets {
+    #[serde(default)]
+    version: u64,
+    #[serde(default)]
+    presets: BTreeMap<String, TradeFilters>,
+}
+
+impl Presets {
+    pub fn load() -> Result<Self, String> {
+        Self::load_from(&presets_path()?)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&presets_path()?)
+    }
+
+    fn load_from(path: &Path) -> Result<Self, String> {
+        match storage::read_json::<Value>(path)? {
+            Some(json) => Self::from_json(json),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), String> {
+        storage::write_json(path, &json!({
+            "version": PRESETS_VERSION,
+            "presets": self.presets,
+        }))
+    }
+
+    fn from_json(json: Value) -> Result<Self, String> {
+        // Files from before the format was versioned have no version
+        let version = json.get("version").and_then(Value::as_u64).unwrap_or(0);
+        if version > PRESETS_VERSION {
+            return Err(format!(
+                "Presets were saved by a newer version of chiseled (format {}, expected {})",
+                version, PRESETS_VERSION
+            ));
+        }
+
+        let mut presets: Self = serde_json::from_value(migrate(json, version))
+            .map_err(|e| format!("Failed to parse presets: {}", e))?;
+        presets.version = PRESETS_VERSION;
+        Ok(presets)
+    }
+
+    /// Applies the change to a copy and keeps it only once it's saved, so a failed save
+    /// leaves the presets in memory matching the ones on disk
+    pub fn update<T>(&mut self, change: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        self.update_with(change, Self::save)
+    }
+
+    fn update_with<T>(
+        &mut self,
+        change: impl FnOnce(&mut Self) -> Result<T, String>,
+        save: impl FnOnce(&Self) -> Result<(), String>,
+    ) -> Result<T, String> {
+        let mut updated = self.clone();
+        let result = change(&mut updated)?;
+        save(&updated)?;
+        *self = updated;
+        Ok(result)
+    }
+
+    /// Saves the filters under the given name, replacing any preset with the same name
+    pub fn insert(&mut self, name: &str, filters: TradeFilters) -> Result<(), String> {
+        let name = validate_name(name)?;
+        self.presets.insert(name, filters);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TradeFilters> {
+        self.presets.get(name)
+    }
+
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        let new_name = validate_name(new_name)?;
+        if new_name == name {
+            return Ok(());
+        }
+        if self.presets.contains_key(&new_name) {
+            return Err(format!("A preset named {} already exists", new_name));
+        }
+
+        let filters = self
+            .presets
+            .remove(name)
+            .ok_or_else(|| format!("No preset named {}", name))?;
+        self.presets.insert(new_name, filters);
+        Ok(())
+    }
+
+    /// Returns false if there was no preset with that name
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.presets.remove(name).is_some()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.presets.keys().cloned().collect()
+    }
+}
+
+// Upgrades presets saved in an older layout to the current one, a version at a time
+fn migrate(mut json: Value, mut version: u64) -> Value {
+    while version < PRESETS_VERSION {
+        json = match version {
+            // Unversioned files were just the map of presets by name
+            0 => json!({ "presets": json }),
+            _ => json,
+        };
+        version += 1;
+    }
+    json
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Preset name can't be empty".to_string());
+    }
+    Ok(name.to_string())
+}
+
+fn presets_path() -> Result<PathBuf, String> {
+    Ok(storage::config_dir()?.join(PRESETS_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::trade_filter::{ListingStatus, SortKey};
+
+    #[test]
+    fn test_presets_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("chiseled-presets-{}", std::process::id()))
+            .join(PRESETS_FILE_NAME);
+
+        let mut presets = Presets::default();
+        presets.insert(" Rings ", TradeFilters::new()).expect("Should save preset");
+        presets.insert("Bows", TradeFilters::new()).expect("Should save preset");
+        assert!(presets.insert("  ", TradeFilters::new()).is_err());
+
+        presets.rename("Rings", "Cheap Rings").expect("Should rename preset");
+        assert!(presets.rename("Bows", "Cheap Rings").is_err());
+        assert!(presets.rename("Amulets", "Cheap Amulets").is_err());
+        assert!(presets.remove("Bows"));
+        assert!(!presets.remove("Bows"));
+
+        presets.save_to(&path).expect("Should write presets");
+        let loaded = Presets::load_from(&path).expect("Should read presets");
+        assert_eq!(loaded.version, PRESETS_VERSION);
+        assert_eq!(loaded.names(), vec!["Cheap Rings".to_string()]);
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_presets_from_older_filters() {
+        // A preset saved before most of the current TradeFilters fields existed
+        let json = json!({
+            "version": 1,
+            "presets": {
+                "Old": {
+                    "item_category": { "text": "accessory.ring", "enabled": true },
+                    "explicit_mods": [],
+                    "implicit_mods": [],
+                    "rune_mods": [],
+                    "price": { "enabled": true, "option": "exalted", "min": null, "max": 5.0 }
+                }
+            }
+        });
+
+        let presets = Presets::from_json(json).expect("Should parse older presets");
+        let filters = presets.get("Old").expect("Preset should exist");
+        assert_eq!(filters.item_category.as_ref().unwrap().text, "accessory.ring");
+        assert_eq!(filters.price.max, Some(5.0));
+        assert_eq!(filters.status.option, ListingStatus::Online);
+        assert_eq!(filters.sort.key, SortKey::Price);
+        assert_eq!(filters.league.as_ref().unwrap().text, "Standard");
+
+        assert!(Presets::from_json(json!({ "version": PRESETS_VERSION + 1 })).is_err());
+    }
+
+    #[test]
+    fn test_migrate_unversioned_presets() {
+        let json = json!({
+            "Rings": { "item_category": { "text": "accessory.ring", "enabled": true } }
+        });
+
+        let presets = Presets::from_json(json).expect("Should migrate unversioned presets");
+        assert_eq!(presets.version, PRESETS_VERSION);
+        assert_eq!(presets.names(), vec!["Rings".to_string()]);
+    }
+
+    #[test]
+    fn test_failed_save_keeps_presets() {
+        let mut presets = Presets::default();
+        presets
+            .update_with(|presets| presets.insert("Rings", TradeFilters::new()), |_| Ok(()))
+            .expect("Should save preset");
+
+        let result = presets.update_with(
+            |presets| presets.rename("Rings", "Cheap Rings"),
+            |_| Err("Disk full".to_string()),
+        );
+        assert!(result.is_err());
+        assert_eq!(presets.names(), vec!["Rings".to_string()]);
+    }
+}
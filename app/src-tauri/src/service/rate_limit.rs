@@ -0,0 +1,371 @@
+use reqwest::header::HeaderMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::TradeError;
+
+// Requests that would have to wait longer than this fail right away, telling the user how long to wait
+const MAX_QUEUE_WAIT: Duration = Duration::from_secs(5);
+
+/// One `hits:period:restriction` entry of an `X-Rate-Limit-<rule>` header
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rule {
+    max_hits: usize,
+    period: Duration,
+}
+
+/// One `hits:period:active restriction` entry of an `X-Rate-Limit-<rule>-State` header
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RuleState {
+    hits: usize,
+    period: Duration,
+    restricted_for: Duration,
+}
+
+/// A rule with the requests that count towards it. Every rule keeps its own hits, since
+/// the server reports each one's state separately and they don't carry over between them.
+#[derive(Debug)]
+struct RuleWindow {
+    // Name of the rule the limit came from, e.g. "ip" or "account"
+    name: String,
+    rule: Rule,
+    // Times of recent requests, oldest first
+    hits: VecDeque<Instant>,
+}
+
+impl RuleWindow {
+    fn hits_within(&self, now: Instant) -> Vec<Instant> {
+        match now.checked_sub(self.rule.period) {
+            Some(start) => self.hits.iter().copied().filter(|hit| *hit > start).collect(),
+            None => self.hits.iter().copied().collect(),
+        }
+    }
+
+    // Forgets requests that no longer count towards the rule
+    fn prune(&mut self, now: Instant) {
+        while let Some(hit) = self.hits.front() {
+            if now.saturating_duration_since(*hit) <= self.rule.period {
+                break;
+            }
+            self.hits.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PolicyState {
+    windows: Vec<RuleWindow>,
+    blocked_until: Option<Instant>,
+}
+
+impl PolicyState {
+    /// How long until another request fits within every rule
+    fn wait_time(&self, now: Instant) -> Duration {
+        let mut wait = self
+            .blocked_until
+            .map(|until| until.saturating_duration_since(now))
+            .unwrap_or_default();
+
+        for window in &self.windows {
+            let max_hits = window.rule.max_hits;
+            if max_hits == 0 {
+                continue;
+            }
+            let in_window = window.hits_within(now);
+            if in_window.len() >= max_hits {
+                // The request that has to leave the window before there's room for another
+                let oldest = in_window[in_window.len() - max_hits];
+                wait = wait.max((oldest + window.rule.period).saturating_duration_since(now));
+            }
+        }
+
+        wait
+    }
+
+    fn record(&mut self, now: Instant) {
+        for window in &mut self.windows {
+            window.hits.push_back(now);
+        }
+    }
+
+    fn update(&mut self, headers: &HeaderMap, now: Instant) {
+        let mut windows = Vec::new();
+        for name in header_str(headers, "x-rate-limit-rules").unwrap_or_default().split(',') {
+            let name = name.trim().to_lowercase();
+            if name.is_empty() {
+                continue;
+            }
+
+            let limits = header_str(headers, &format!("x-rate-limit-{}", name))
+                .map(parse_rules)
+                .unwrap_or_default();
+            let states = header_str(headers, &format!("x-rate-limit-{}-state", name))
+                .map(parse_rule_states)
+                .unwrap_or_default();
+
+            for rule in limits {
+                // Keep the hits we already know of for rules that didn't change
+                let hits = self
+                    .windows
+                    .iter()
+                    .position(|window| window.name == name && window.rule.period == rule.period)
+                    .map(|i| self.windows.swap_remove(i).hits)
+                    .unwrap_or_default();
+                windows.push(RuleWindow {
+                    name: name.clone(),
+                    rule,
+                    hits,
+                });
+            }
+
+            for state in states {
+                // Requests from elsewhere (another instance, the trade site itself) count too,
+                // so make up for any hits the server saw for this rule but we didn't
+                let window = windows
+                    .iter_mut()
+                    .find(|window| window.name == name && window.rule.period == state.period);
+                if let Some(window) = window {
+                    let known = window.hits_within(now).len();
+                    for _ in known..state.hits {
+                        window.hits.push_back(now);
+                    }
+                }
+
+                if !state.restricted_for.is_zero() {
+                    self.block_for(state.restricted_for, now);
+                }
+            }
+        }
+
+        if let Some(retry_after) = header_str(headers, "retry-after").and_then(|value| value.trim().parse().ok()) {
+            self.block_for(Duration::from_secs(retry_after), now);
+        }
+
+        if !windows.is_empty() {
+            self.windows = windows;
+        }
+        for window in &mut self.windows {
+            window.prune(now);
+        }
+    }
+
+    fn block_for(&mut self, duration: Duration, now: Instant) {
+        let until = now + duration;
+        self.blocked_until = Some(self.blocked_until.map_or(until, |blocked_until| blocked_until.max(until)));
+    }
+}
+
+/// Keeps requests under the trade site's rate limits, which are reported per policy in the
+/// `X-Rate-Limit-*` headers of every response. Shared by everything that talks to the trade API.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    policies: Mutex<HashMap<String, PolicyState>>,
+}
+
+impl RateLimiter {
+    /// Waits until a request for the policy fits within its limits and records it.
+    /// Fails with the time left instead when that would take too long.
+    pub async fn acquire(&self, policy: &str) -> Result<(), TradeError> {
+        loop {
+            let wait = {
+                let mut policies = self
+                    .policies
+                    .lock()
+                    .map_err(|e| format!("Failed to read rate limits: {}", e))?;
+                let state = policies.entry(policy.to_string()).or_default();
+
+                let now = Instant::now();
+                let wait = state.wait_time(now);
+                if wait.is_zero() {
+                    state.record(now);
+                    return Ok(());
+                }
+                wait
+            };
+
+            if wait > MAX_QUEUE_WAIT {
+                return Err(rate_limited_error(wait));
+            }
+
+            log::info!("Delaying {} request by {}ms to stay under the rate limit", policy, wait.as_millis());
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Updates the limits and state of the policy from a response's headers
+    pub fn update(&self, policy: &str, headers: &HeaderMap) {
+        match self.policies.lock() {
+            Ok(mut policies) => policies
+                .entry(policy.to_string())
+                .or_default()
+                .update(headers, Instant::now()),
+            Err(e) => log::warn!("Failed to update rate limits: {}", e),
+        }
+    }
+
+    /// Error for a request the trade site turned down for going over the limit
+    pub fn rate_limited_error(&self, policy: &str) -> TradeError {
+        let wait = self
+            .policies
+            .lock()
+            .ok()
+            .and_then(|policies| policies.get(policy).map(|state| state.wait_time(Instant::now())))
+            .unwrap_or_default();
+        rate_limited_error(wait)
+    }
+}
+
+fn rate_limited_error(wait: Duration) -> TradeError {
+    // Round up so we never tell the user to retry before the limit has actually passed
+    let seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    TradeError::RateLimited {
+        retry_after: seconds.max(1),
+    }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn parse_triples(value: &str) -> impl Iterator<Item = (u64, u64, u64)> + '_ {
+    value.split(',').filter_map(|entry| {
+        let mut parts = entry.trim().split(':').map(|part| part.parse::<u64>().ok());
+        Some((parts.next()??, parts.next()??, parts.next()??))
+    })
+}
+
+fn parse_rules(value: &str) -> Vec<Rule> {
+    parse_triples(value)
+        // The third value is how long going over the limit times us out for, which the
+        // state header reports once it happens
+        .map(|(max_hits, period, _)| Rule {
+            max_hits: max_hits as usize,
+            period: Duration::from_secs(period),
+        })
+        .collect()
+}
+
+fn parse_rule_states(value: &str) -> Vec<RuleState> {
+    parse_triples(value)
+        .map(|(hits, period, restricted_for)| RuleState {
+            hits: hits as usize,
+            period: Duration::from_secs(period),
+            restricted_for: Duration::from_secs(restricted_for),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(entries: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in entries {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(
+            parse_rules("8:10:60,15:60:120"),
+            vec![
+                Rule {
+                    max_hits: 8,
+                    period: Duration::from_secs(10),
+                },
+                Rule {
+                    max_hits: 15,
+                    period: Duration::from_secs(60),
+                },
+            ]
+        );
+        assert_eq!(parse_rules("8:10,bad"), vec![]);
+    }
+
+    #[test]
+    fn test_wait_for_full_window() {
+        let now = Instant::now();
+        let mut state = PolicyState::default();
+        state.update(
+            &headers(&[
+                ("x-rate-limit-rules", "Ip"),
+                ("x-rate-limit-ip", "3:10:60"),
+                ("x-rate-limit-ip-state", "1:10:0"),
+            ]),
+            now,
+        );
+        assert_eq!(state.wait_time(now), Duration::ZERO);
+
+        // Fill the window, the next request has to wait for the oldest one to leave it
+        state.record(now + Duration::from_secs(2));
+        state.record(now + Duration::from_secs(4));
+        assert_eq!(state.wait_time(now + Duration::from_secs(4)), Duration::from_secs(6));
+        assert_eq!(state.wait_time(now + Duration::from_secs(11)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_server_state_and_restrictions() {
+        let now = Instant::now();
+        let mut state = PolicyState::default();
+
+        // The server has seen more requests than we sent ourselves
+        state.update(
+            &headers(&[
+                ("x-rate-limit-rules", "Ip,Account"),
+                ("x-rate-limit-ip", "5:10:60"),
+                ("x-rate-limit-ip-state", "5:10:0"),
+                ("x-rate-limit-account", "30:300:600"),
+                ("x-rate-limit-account-state", "5:300:0"),
+            ]),
+            now,
+        );
+        assert_eq!(state.windows.len(), 2);
+        assert_eq!(state.wait_time(now), Duration::from_secs(10));
+
+        // Active restrictions and Retry-After block every request until they pass
+        state.update(
+            &headers(&[
+                ("x-rate-limit-rules", "Ip"),
+                ("x-rate-limit-ip", "5:10:60"),
+                ("x-rate-limit-ip-state", "6:10:60"),
+                ("retry-after", "90"),
+            ]),
+            now,
+        );
+        assert_eq!(state.wait_time(now), Duration::from_secs(90));
+        assert_eq!(
+            rate_limited_error(Duration::from_millis(89_500)).to_string(),
+            "Trade site rate limit reached, try again in 90s"
+        );
+    }
+
+    #[test]
+    fn test_rules_count_their_own_hits() {
+        let now = Instant::now();
+        let mut state = PolicyState::default();
+
+        // Many requests over the last few minutes, but only one in the short window
+        state.update(
+            &headers(&[
+                ("x-rate-limit-rules", "Ip"),
+                ("x-rate-limit-ip", "5:10:60,30:300:600"),
+                ("x-rate-limit-ip-state", "1:10:0,20:300:0"),
+            ]),
+            now,
+        );
+        assert_eq!(state.wait_time(now), Duration::ZERO);
+
+        // Hits made up for the long window don't fill the short one
+        for _ in 0..3 {
+            state.record(now);
+        }
+        assert_eq!(state.wait_time(now), Duration::ZERO);
+        state.record(now);
+        assert_eq!(state.wait_time(now), Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,116 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::model::trade_query::TradeQuery;
+use crate::model::trade_result::ItemListing;
+
+// Listings sell and get repriced, so searches are only reused for a little while
+const SEARCH_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// A search posted to the trade site, kept so further pages only need `/fetch` requests
+#[derive(Debug)]
+pub struct CachedSearch {
+    /// Search id from the trade site, as used in trade site links
+    pub id: String,
+    /// Every result id of the search, in the order the trade site sorted them
+    pub result_ids: Vec<String>,
+    listings: Mutex<HashMap<String, ItemListing>>,
+    created_at: Instant,
+}
+
+impl CachedSearch {
+    pub fn new(id: String, result_ids: Vec<String>) -> Self {
+        Self {
+            id,
+            result_ids,
+            listings: Mutex::new(HashMap::new()),
+            created_at: Instant::now(),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.created_at.elapsed() > SEARCH_TTL
+    }
+
+    /// Already fetched listings for the ids, None for ids that still need fetching
+    pub fn listings(&self, ids: &[&str]) -> Vec<Option<ItemListing>> {
+        match self.listings.lock() {
+            Ok(listings) => ids.iter().map(|id| listings.get(*id).cloned()).collect(),
+            Err(_) => vec![None; ids.len()],
+        }
+    }
+
+    /// How many of the listings fetched so far match
+    pub fn count_listings(&self, predicate: impl Fn(&ItemListing) -> bool) -> usize {
+        match self.listings.lock() {
+            Ok(listings) => listings.values().filter(|listing| predicate(listing)).count(),
+            Err(_) => 0,
+        }
+    }
+
+    pub fn store_listings(&self, fetched: &[ItemListing]) {
+        if let Ok(mut listings) = self.listings.lock() {
+            for listing in fetched {
+                listings.insert(listing.id.clone(), listing.clone());
+            }
+        }
+    }
+}
+
+/// Recent searches keyed by a hash of their query
+#[derive(Debug, Default)]
+pub struct SearchCache {
+    searches: Mutex<HashMap<String, Arc<CachedSearch>>>,
+}
+
+impl SearchCache {
+    pub fn get(&self, query: &TradeQuery) -> Option<Arc<CachedSearch>> {
+        let searches = self.searches.lock().ok()?;
+        searches
+            .get(&query_hash(query))
+            .filter(|search| !search.is_expired())
+            .cloned()
+    }
+
+    pub fn insert(&self, query: &TradeQuery, search: CachedSearch) -> Arc<CachedSearch> {
+        let search = Arc::new(search);
+        if let Ok(mut searches) = self.searches.lock() {
+            searches.retain(|_, search| !search.is_expired());
+            searches.insert(query_hash(query), search.clone());
+        }
+        search
+    }
+}
+
+fn query_hash(query: &TradeQuery) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(query).unwrap_or_default());
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::trade_filter::{OptionFilter, SaleType, TradeFilters};
+
+    #[test]
+    fn test_search_cache_by_query() {
+        let cache = SearchCache::default();
+        let mut filters = TradeFilters::new();
+        let query = TradeQuery::from_trade_filters(&filters);
+        assert!(cache.get(&query).is_none());
+
+        cache.insert(&query, CachedSearch::new("AbC123".to_string(), vec!["a".to_string()]));
+        let search = cache.get(&TradeQuery::from_trade_filters(&filters)).expect("Should reuse search");
+        assert_eq!(search.id, "AbC123");
+        assert!(search.listings(&["a"])[0].is_none());
+
+        // Any change to the query is a different search
+        filters.sale_type = OptionFilter {
+            option: SaleType::Priced,
+        };
+        assert!(cache.get(&TradeQuery::from_trade_filters(&filters)).is_none());
+    }
+}
@@ -0,0 +1,47 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Matches the bundle identifier so our files live next to the ones tauri creates
+const APP_DIR_NAME: &str = "com.makestuffwithme.chiseled";
+
+pub fn data_dir() -> Result<PathBuf, String> {
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_DIR_NAME))
+        .ok_or_else(|| "Failed to locate app data directory".to_string())
+}
+
+pub fn config_dir() -> Result<PathBuf, String> {
+    dirs::config_dir()
+        .map(|dir| dir.join(APP_DIR_NAME))
+        .ok_or_else(|| "Failed to locate app config directory".to_string())
+}
+
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let text = serde_json::to_string(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+
+    // Write to a temporary file first so a crash mid-write never leaves a truncated file behind
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, text)
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
@@ -1,9 +1,25 @@
-use reqwest::Client;
+use reqwest::{RequestBuilder, StatusCode};
 use serde_json::Value;
 use serde::{Deserialize, Serialize};
-
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::TradeError;
+use crate::model::exchange::{ExchangeQuery, ExchangeResult};
 use crate::model::trade_query::TradeQuery;
-use crate::model::trade_result::TradeResult;
+use crate::model::trade_result::{ItemListing, TradeResult};
+use crate::service::blocklist::Blocklist;
+use crate::service::search_cache::{CachedSearch, SearchCache};
+use crate::service::trade_client::TradeClient;
+
+// Rate limit policies, the trade site limits each kind of request separately
+const DATA_POLICY: &str = "data";
+const SEARCH_POLICY: &str = "search";
+const FETCH_POLICY: &str = "fetch";
+const EXCHANGE_POLICY: &str = "exchange";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct League {
@@ -17,159 +33,547 @@ pub struct LeaguesResponse {
     pub result: Vec<League>,
 }
 
-async fn check_error_response(response_text: &str) -> Result<Value, String> {
-    if !response_text.starts_with('{') {
-        return Err(format!("Invalid JSON response: {}", response_text));
-    }
-
-    let json: Value = serde_json::from_str(response_text)
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+fn check_error_response(status: StatusCode, response_text: &str) -> Result<Value, TradeError> {
+    let json = match serde_json::from_str::<Value>(response_text) {
+        Ok(json) if json.is_object() => json,
+        // Errors from in front of the API (e.g. Cloudflare) come as HTML pages
+        _ if !status.is_success() => return Err(TradeError::from_api(status, None, response_text.to_string())),
+        _ => {
+            return Err(TradeError::InvalidResponse {
+                message: format!("Invalid JSON response: {}", response_text),
+            })
+        }
+    };
 
     if let Some(error) = json.get("error") {
-        if let Some(message) = error.get("message") {
-            return Err(message.as_str().unwrap_or("Unknown error").to_string());
-        }
+        let code = error.get("code").and_then(Value::as_u64);
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("Unknown error")
+            .to_string();
+        return Err(TradeError::from_api(status, code, message));
     }
 
     Ok(json)
 }
 
-pub async fn fetch_mappings(client: &Client) -> Result<(Value, Value), String> {
-    async fn fetch_stats(client: &Client) -> Result<Value, String> {
-        let response = client
-            .get("https://www.pathofexile.com/api/trade2/data/stats")
-            .send()
-            .await
-            .map_err(|e| format!("Stats request failed: {}", e))?;
-
-        let text = response.text().await.map_err(|e| e.to_string())?;
-        check_error_response(&text).await
+// Transient failures (timeouts, dropped connections, 5xx) are retried this many times
+const MAX_RETRIES: u32 = 2;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Sends a request once the rate limiter allows it and returns the response JSON,
+/// retrying transient failures with a growing, jittered delay
+async fn send(client: &TradeClient, policy: &str, name: &str, request: RequestBuilder) -> Result<Value, TradeError> {
+    let mut attempt = 0;
+    loop {
+        // Our bodies are always JSON, so requests can be cloned
+        let attempt_request = request
+            .try_clone()
+            .ok_or_else(|| format!("{} request can't be sent again", name))?;
+
+        match send_once(client, policy, name, attempt_request).await {
+            Err(e) if attempt < MAX_RETRIES && e.is_transient() => {
+                attempt += 1;
+                let delay = retry_delay(attempt);
+                log::info!("{} request failed, retrying in {}ms: {}", name, delay.as_millis(), e);
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
     }
+}
 
-    async fn fetch_items(client: &Client) -> Result<Value, String> {
-        let response = client
-            .get("https://www.pathofexile.com/api/trade2/data/items")
-            .send()
-            .await
-            .map_err(|e| format!("Items request failed: {}", e))?;
+async fn send_once(client: &TradeClient, policy: &str, name: &str, request: RequestBuilder) -> Result<Value, TradeError> {
+    let network_error = |e: reqwest::Error| {
+        if e.is_timeout() {
+            TradeError::TimedOut {
+                request: name.to_string(),
+            }
+        } else {
+            TradeError::Network {
+                request: name.to_string(),
+                message: e.to_string(),
+            }
+        }
+    };
 
-        let text = response.text().await.map_err(|e| e.to_string())?;
-        check_error_response(&text).await
+    let limiter = &client.limiter;
+    limiter.acquire(policy).await?;
+    let response = request.send().await.map_err(network_error)?;
+
+    limiter.update(policy, response.headers());
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(limiter.rate_limited_error(policy));
     }
 
-    tokio::try_join!(fetch_stats(client), fetch_items(client))
+    let text = response.text().await.map_err(network_error)?;
+    check_error_response(status, &text).map_err(|e| match e {
+        // The error body doesn't say how long to wait, the headers we just read do
+        TradeError::RateLimited { .. } => limiter.rate_limited_error(policy),
+        e => e,
+    })
 }
 
-pub async fn fetch_leagues(client: &Client) -> Result<Vec<League>, String> {
-    let response = client
-        .get("https://www.pathofexile.com/api/trade2/data/leagues")
-        .send()
-        .await
-        .map_err(|e| format!("Leagues request failed: {}", e))?;
+// Doubles with every attempt, plus up to one base delay of jitter so clients that failed
+// together don't all retry at the same moment
+fn retry_delay(attempt: u32) -> Duration {
+    let backoff = RETRY_BASE_DELAY * 2u32.pow(attempt.saturating_sub(1));
+    // RandomState is seeded randomly every time, which is random enough for jitter
+    let random = RandomState::new().build_hasher().finish();
+    backoff + RETRY_BASE_DELAY.mul_f64((random % 1000) as f64 / 1000.0)
+}
+
+pub async fn fetch_mappings(client: &TradeClient) -> Result<(Value, Value, Value), TradeError> {
+    async fn fetch_data(client: &TradeClient, kind: &str, name: &str) -> Result<Value, TradeError> {
+        let request = client.http.get(client.endpoints.data_url(kind));
+        send(client, DATA_POLICY, name, request).await
+    }
+
+    tokio::try_join!(
+        fetch_data(client, "stats", "Stats"),
+        fetch_data(client, "items", "Items"),
+        fetch_data(client, "static", "Static")
+    )
+}
 
-    let text = response.text().await.map_err(|e| e.to_string())?;
-    let json = check_error_response(&text).await?;
+pub async fn fetch_leagues(client: &TradeClient) -> Result<Vec<League>, TradeError> {
+    let request = client.http.get(client.endpoints.data_url("leagues"));
+    let json = send(client, DATA_POLICY, "Leagues", request).await?;
     
-    let leagues_response: LeaguesResponse = serde_json::from_value(json)
-        .map_err(|e| format!("Failed to parse leagues response: {}", e))?;
+    let leagues_response: LeaguesResponse = serde_json::from_value(json).map_err(|e| TradeError::InvalidResponse {
+        message: format!("Failed to parse leagues response: {}", e),
+    })?;
     
+    // Console leagues are listed too, searches in them go to the league's own realm
     Ok(leagues_response.result)
 }
 
-pub async fn search_trade(client: &Client, query: &TradeQuery, page: u32) -> Result<String, String> {
+// Listings per page, also the most ids a single fetch request takes
+pub const PAGE_SIZE: usize = 10;
+
+// Listings the price summary looks at, a few pages' worth without costing many requests
+pub const SUMMARY_SAMPLE_SIZE: usize = 40;
+
+/// Posts the search, or reuses a recent identical one from the cache
+pub async fn search(client: &TradeClient, cache: &SearchCache, query: &TradeQuery) -> Result<Arc<CachedSearch>, TradeError> {
+    if let Some(search) = cache.get(query) {
+        log::info!("Reusing search {}", search.id);
+        return Ok(search);
+    }
+
     // Search for items
-    let encoded_league = urlencoding::encode(&query.league);
-    let response = client
-        .post(format!("https://www.pathofexile.com/api/trade2/search/{}", encoded_league))
-        .json(&query)
-        .send()
-        .await
-        .map_err(|e| format!("Search request failed: {}", e))?;
+    let request = client
+        .http
+        .post(client.endpoints.search_url(query.realm.as_deref(), &query.league))
+        .json(&query);
 
-    log::info!("Search query: {}", &query.query);
+    log::info!("Search query: {}", serde_json::to_string(&query.query).unwrap_or_default());
 
-    let search_text = response.text().await.map_err(|e| e.to_string())?;
-    let search_json = check_error_response(&search_text).await?;
+    let search_json = send(client, SEARCH_POLICY, "Search", request).await?;
 
     log::info!("Search JSON: {}", search_json);
 
-    // Calculate pagination offsets
-    let per_page = 10;
-    let start_idx = (page as usize - 1) * per_page;
-    let total_results = search_json["result"]
-        .as_array()
-        .map(|arr| arr.len())
-        .unwrap_or(0);
-
-    // Extract paginated result IDs
     let result_ids = search_json["result"]
         .as_array()
-        .ok_or("No results found")?
+        .ok_or(TradeError::NoResults)?
         .iter()
-        .skip(start_idx)
-        .take(per_page)
-        .map(|v| v.as_str().unwrap_or_default())
-        .collect::<Vec<_>>()
-        .join(",");
+        .map(|v| v.as_str().unwrap_or_default().to_string())
+        .collect();
+    let id = search_json["id"].as_str().unwrap_or_default().to_string();
+
+    Ok(cache.insert(query, CachedSearch::new(id, result_ids)))
+}
 
-    if result_ids.is_empty() {
-        return Err("No results found".to_string());
+/// Gets a page of listings for the search, fetching only the ones that aren't cached yet.
+/// Pages follow the order of `ids`, the search's `result_ids` for the trade site's own order.
+pub async fn fetch_page(
+    client: &TradeClient,
+    search: &CachedSearch,
+    ids: &[String],
+    page: u32,
+    blocklist: &Blocklist,
+) -> Result<TradeResult, TradeError> {
+    if page < 1 {
+        return Err(format!("Invalid page {}, pages start at 1", page).into());
     }
+    let all_ids = ids.iter().map(String::as_str).collect::<Vec<_>>();
 
-    log::info!("Result IDs: {}", result_ids);
+    // Calculate pagination offsets
+    let total_results = all_ids.len();
+    let start_idx = (page as usize - 1) * PAGE_SIZE;
 
-    // Fetch item details
-    let fetch_response = client
-        .get(format!(
-            "https://www.pathofexile.com/api/trade2/fetch/{}",
-            result_ids
-        ))
-        .send()
-        .await
-        .map_err(|e| format!("Fetch request failed: {}", e))?;
-
-    let fetch_text = fetch_response.text().await.map_err(|e| e.to_string())?;
-    check_error_response(&fetch_text).await?;
-
-    // Parse into our TradeResult struct and add pagination metadata
-    let mut trade_result: TradeResult = match serde_json::from_str(&fetch_text) {
-        Ok(result) => result,
-        Err(e) => {
-            log::error!("Failed to parse fetch response: {}", e);
-            log::error!("Raw response: {}", fetch_text);
-            return Err(format!(
-                "Failed to parse fetch response into TradeResult: {}\nRaw response: {}",
-                e, fetch_text
-            ));
+    let listings = if blocklist.is_empty() {
+        let page_ids = all_ids.iter().skip(start_idx).take(PAGE_SIZE).copied().collect::<Vec<_>>();
+        if page_ids.is_empty() {
+            return Err(TradeError::NoResults);
+        }
+        cached_listings(client, search, &page_ids).await?
+    } else {
+        // Blocked sellers shift every later listing forward, so walk the ids from the start
+        // and count only visible listings until the requested page is filled
+        let mut to_skip = start_idx;
+        let mut listings = Vec::with_capacity(PAGE_SIZE);
+        for batch in all_ids.chunks(PAGE_SIZE) {
+            for listing in cached_listings(client, search, batch).await? {
+                if blocklist.is_blocked(&listing.listing.account.name) {
+                    continue;
+                }
+                if to_skip > 0 {
+                    to_skip -= 1;
+                    continue;
+                }
+                listings.push(listing);
+            }
+            if listings.len() >= PAGE_SIZE {
+                break;
+            }
+        }
+        listings.truncate(PAGE_SIZE);
+        if listings.is_empty() {
+            return Err(TradeError::NoResults);
         }
+        listings
     };
 
-    // Add pagination metadata to the result
-    trade_result.total = total_results;
-    trade_result.current_page = page as usize;
-    trade_result.total_pages = (total_results + per_page - 1) / per_page;
+    // Only fetched listings are known to be blocked, so the totals can still shrink on later pages
+    let hidden = if blocklist.is_empty() {
+        0
+    } else {
+        search.count_listings(|listing| blocklist.is_blocked(&listing.listing.account.name))
+    };
+    let total = total_results - hidden;
+
+    Ok(TradeResult {
+        result: listings,
+        total,
+        current_page: page as usize,
+        total_pages: total.div_ceil(PAGE_SIZE),
+        query_id: search.id.clone(),
+        hidden,
+        summary: None,
+    })
+}
 
-    // Convert back to string to return
-    let result_str = serde_json::to_string(&trade_result)
-        .map_err(|e| format!("Failed to serialize TradeResult: {}", e))?;
+/// Priced listings among the search's first `count` results that are already fetched,
+/// skipping blocked sellers. Never sends a request, so the summary grows as pages are loaded.
+pub fn sample_listings(search: &CachedSearch, count: usize, blocklist: &Blocklist) -> Vec<ItemListing> {
+    let ids = search.result_ids.iter().take(count).map(String::as_str).collect::<Vec<_>>();
+
+    search
+        .listings(&ids)
+        .into_iter()
+        .flatten()
+        .filter(|listing| listing.listing.price.is_some())
+        .filter(|listing| !blocklist.is_blocked(&listing.listing.account.name))
+        .collect()
+}
 
-    log::info!("Final result: {}", result_str);
-    Ok(result_str)
+/// Fetches whatever `sample_listings` is still missing of the search's first `count` results,
+/// `PAGE_SIZE` ids per request
+pub async fn fetch_sample(client: &TradeClient, search: &CachedSearch, count: usize) -> Result<(), TradeError> {
+    let ids = search.result_ids.iter().take(count).map(String::as_str).collect::<Vec<_>>();
+    for batch in ids.chunks(PAGE_SIZE) {
+        cached_listings(client, search, batch).await?;
+    }
+    Ok(())
 }
 
-pub async fn get_query_id(client: &Client, query: &TradeQuery) -> Result<String, String> {
-    let encoded_league = urlencoding::encode(&query.league);
-    let response = client
-        .post(format!("https://www.pathofexile.com/api/trade2/search/{}", encoded_league))
-        .json(&query)
-        .send()
-        .await
-        .map_err(|e| format!("Search request failed: {}", e))?;
+// Listings for the ids in order, fetching the ones the search hasn't seen yet
+async fn cached_listings(client: &TradeClient, search: &CachedSearch, ids: &[&str]) -> Result<Vec<ItemListing>, TradeError> {
+    let cached = search.listings(ids);
+    let missing = ids
+        .iter()
+        .zip(&cached)
+        .filter(|(_, listing)| listing.is_none())
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+
+    let mut fetched = HashMap::new();
+    if !missing.is_empty() {
+        let listings = fetch_listings(client, &missing).await?.result;
+        search.store_listings(&listings);
+        fetched = listings.into_iter().map(|listing| (listing.id.clone(), listing)).collect();
+    }
 
-    let search_text = response.text().await.map_err(|e| e.to_string())?;
-    let search_json = check_error_response(&search_text).await?;
+    // Ids the trade site didn't return (e.g. listings removed since the search) are skipped
+    Ok(ids
+        .iter()
+        .zip(cached)
+        .filter_map(|(id, listing)| listing.or_else(|| fetched.remove(*id)))
+        .collect())
+}
 
-    log::info!("Search JSON: {}", search_json);
+/// Fetches listings by id, at most `PAGE_SIZE` per call
+pub async fn fetch_listings(client: &TradeClient, ids: &[&str]) -> Result<TradeResult, TradeError> {
+    log::info!("Result IDs: {}", ids.join(","));
+
+    // Fetch item details
+    let request = client.http.get(client.endpoints.fetch_url(ids));
+    let fetch_json = send(client, FETCH_POLICY, "Fetch", request).await?;
+
+    // Parse into our TradeResult struct
+    TradeResult::deserialize(&fetch_json).map_err(|e| {
+        log::error!("Failed to parse fetch response: {}", e);
+        log::error!("Raw response: {}", fetch_json);
+        TradeError::InvalidResponse {
+            message: format!("Failed to parse fetch response into TradeResult: {}", e),
+        }
+    })
+}
+
+/// Searches the bulk exchange, which lists offers with their ratio and stock in one response
+pub async fn exchange(client: &TradeClient, query: &ExchangeQuery) -> Result<ExchangeResult, TradeError> {
+    let request = client
+        .http
+        .post(client.endpoints.exchange_url(query.realm.as_deref(), &query.league))
+        .json(query);
+
+    log::info!("Exchange query: {}", serde_json::to_string(&query.query).unwrap_or_default());
+
+    let json = send(client, EXCHANGE_POLICY, "Exchange", request).await?;
+    let result = ExchangeResult::from_response(json, &query.query.have).map_err(|message| TradeError::InvalidResponse { message })?;
+    if result.offers.is_empty() {
+        return Err(TradeError::NoResults);
+    }
+    Ok(result)
+}
+
+/// Fetches the query behind a shared search id
+pub async fn fetch_search(
+    client: &TradeClient,
+    realm: Option<&str>,
+    league: &str,
+    id: &str,
+) -> Result<TradeQuery, TradeError> {
+    let request = client.http.get(client.endpoints.saved_search_url(realm, league, id));
+    let search_json = send(client, SEARCH_POLICY, "Search", request).await?;
+
+    TradeQuery::from_search_json(search_json, realm.map(str::to_string), league.to_string())
+        .map_err(|message| TradeError::InvalidResponse { message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::trade_filter::{TextFilter, TradeFilters};
+    use crate::service::endpoints::Endpoints;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    fn mock_server(responses: Vec<&str>) -> (Endpoints, mpsc::Receiver<String>) {
+        mock_server_with_status(responses.into_iter().map(|body| (200, body)).collect())
+    }
+
+    // Serves one canned status and JSON body per request and reports each request line back to the test
+    fn mock_server_with_status(responses: Vec<(u16, &str)>) -> (Endpoints, mpsc::Receiver<String>) {
+        let responses = responses
+            .into_iter()
+            .map(|(status, body)| (status, body.to_string()))
+            .collect::<Vec<_>>();
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Should bind mock server");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, requests) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let Ok((stream, _)) = listener.accept() else { return };
+                let mut reader = BufReader::new(stream);
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut request_body = vec![0; content_length];
+                reader.read_exact(&mut request_body).unwrap();
+
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+                sender.send(request_line.trim().to_string()).unwrap();
+            }
+        });
+
+        let endpoints = Endpoints {
+            base_url,
+            ..Endpoints::default()
+        };
+        (endpoints, requests)
+    }
 
-    Ok(search_json["id"].as_str().unwrap_or_default().to_string())
+    #[test]
+    fn test_retry_delay_grows_with_jitter() {
+        for attempt in 1..=3 {
+            let delay = retry_delay(attempt);
+            let backoff = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+            assert!(delay >= backoff && delay < backoff + RETRY_BASE_DELAY);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_leagues_from_mock_server() {
+        let (endpoints, requests) = mock_server(vec![
+            r#"{"result": [
+                {"id": "Standard", "realm": "poe2", "text": "Standard"},
+                {"id": "Standard", "realm": "xbox", "text": "Standard"}
+            ]}"#,
+        ]);
+        let client = TradeClient::new(endpoints).unwrap();
+
+        let leagues = fetch_leagues(&client).await.expect("Should fetch leagues");
+        assert_eq!(requests.recv().unwrap(), "GET /api/trade2/data/leagues HTTP/1.1");
+        assert_eq!(leagues.len(), 2);
+        assert_eq!(leagues[1].realm, "xbox");
+    }
+
+    #[tokio::test]
+    async fn test_retry_server_errors() {
+        let (endpoints, requests) = mock_server_with_status(vec![
+            (503, "<html>Service Unavailable</html>"),
+            (200, r#"{"result": [{"id": "Standard", "realm": "poe2", "text": "Standard"}]}"#),
+        ]);
+        let client = TradeClient::new(endpoints).unwrap();
+
+        let leagues = fetch_leagues(&client).await.expect("Should succeed after a retry");
+        assert_eq!(leagues.len(), 1);
+        assert_eq!(requests.iter().count(), 2);
+
+        // Errors the trade site means, like a bad query, aren't retried
+        let (endpoints, requests) = mock_server_with_status(vec![(
+            400,
+            r#"{"error": {"code": 2, "message": "Invalid query"}}"#,
+        )]);
+        let client = TradeClient::new(endpoints).unwrap();
+        let error = fetch_leagues(&client).await.unwrap_err();
+        assert!(matches!(error, TradeError::InvalidQuery { code: 2, .. }));
+        assert_eq!(requests.iter().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_in_realm_from_mock_server() {
+        let response = r#"{"id": "AbC123", "result": ["a", "b"], "total": 2}"#;
+        let (mut endpoints, requests) = mock_server(vec![response, response]);
+        endpoints.realm = "xbox".to_string();
+        let client = TradeClient::new(endpoints).unwrap();
+        let cache = SearchCache::default();
+        let mut filters = TradeFilters::new();
+        let query = TradeQuery::from_trade_filters(&filters);
+
+        let search = search(&client, &cache, &query).await.expect("Should search");
+        assert_eq!(
+            requests.recv().unwrap(),
+            format!("POST /api/trade2/search/xbox/{} HTTP/1.1", urlencoding::encode(&query.league))
+        );
+        assert_eq!(search.id, "AbC123");
+        assert_eq!(search.result_ids, vec!["a", "b"]);
+
+        // A league's own realm wins over the default one
+        filters.realm = Some(TextFilter {
+            text: "sony".to_string(),
+            enabled: true,
+        });
+        let query = TradeQuery::from_trade_filters(&filters);
+        super::search(&client, &cache, &query).await.expect("Should search");
+        assert_eq!(
+            requests.recv().unwrap(),
+            format!("POST /api/trade2/search/sony/{} HTTP/1.1", urlencoding::encode(&query.league))
+        );
+    }
+
+    fn listing_json(id: &str, seller: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "listing": {
+                "method": "psapi",
+                "indexed": "2025-02-07T02:26:42Z",
+                "account": {"name": seller},
+                "price": {"type": "~price", "amount": 1.0, "currency": "exalted"}
+            },
+            "item": {
+                "realm": "poe2",
+                "verified": true,
+                "w": 1,
+                "h": 1,
+                "icon": "",
+                "league": "Standard",
+                "id": id,
+                "name": "",
+                "typeLine": "Gold Ring",
+                "baseType": "Gold Ring",
+                "frameType": 2
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_blocked_sellers_left_out_of_totals() {
+        let sellers = [("a", "Seller#1"), ("b", "Blocked#2"), ("c", "Seller#3"), ("d", "Blocked#2")];
+        let fetched = serde_json::json!({
+            "result": sellers.iter().map(|(id, seller)| listing_json(id, seller)).collect::<Vec<_>>()
+        })
+        .to_string();
+        let (endpoints, _requests) = mock_server(vec![&fetched]);
+        let client = TradeClient::new(endpoints).unwrap();
+        let search = CachedSearch::new("AbC123".to_string(), sellers.iter().map(|(id, _)| id.to_string()).collect());
+        let mut blocklist = Blocklist::default();
+        blocklist.block("Blocked#2");
+        assert!(sample_listings(&search, SUMMARY_SAMPLE_SIZE, &blocklist).is_empty());
+
+        assert!(matches!(
+            fetch_page(&client, &search, &search.result_ids, 0, &blocklist).await,
+            Err(TradeError::Other { .. })
+        ));
+
+        let page = fetch_page(&client, &search, &search.result_ids, 1, &blocklist).await.expect("Should fetch page");
+        let ids = page.result.iter().map(|listing| listing.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "c"]);
+        assert_eq!(page.total, 2);
+        assert_eq!(page.hidden, 2);
+        assert_eq!(page.total_pages, 1);
+
+        // The summary sample only reuses what the page already fetched
+        let sample = sample_listings(&search, SUMMARY_SAMPLE_SIZE, &blocklist);
+        assert_eq!(sample.iter().map(|listing| listing.id.as_str()).collect::<Vec<_>>(), vec!["a", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_first_search_summarizes_more_than_a_page() {
+        let ids = (0..PAGE_SIZE + 5).map(|i| format!("id{}", i)).collect::<Vec<_>>();
+        let batch = |ids: &[String]| {
+            serde_json::json!({
+                "result": ids.iter().map(|id| listing_json(id, "Seller#1")).collect::<Vec<_>>()
+            })
+            .to_string()
+        };
+        let (first_page, rest) = (batch(&ids[..PAGE_SIZE]), batch(&ids[PAGE_SIZE..]));
+        let (endpoints, requests) = mock_server(vec![&first_page, &rest]);
+        let client = TradeClient::new(endpoints).unwrap();
+        let search = CachedSearch::new("AbC123".to_string(), ids.clone());
+        let blocklist = Blocklist::default();
+
+        fetch_page(&client, &search, &search.result_ids, 1, &blocklist).await.expect("Should fetch page");
+        fetch_sample(&client, &search, SUMMARY_SAMPLE_SIZE).await.expect("Should fetch sample");
+        assert_eq!(sample_listings(&search, SUMMARY_SAMPLE_SIZE, &blocklist).len(), PAGE_SIZE + 5);
+
+        // The first page was cached, so only the rest of the sample is requested
+        assert_eq!(requests.iter().count(), 2);
+        fetch_sample(&client, &search, SUMMARY_SAMPLE_SIZE).await.expect("Sample should be cached");
+    }
 }
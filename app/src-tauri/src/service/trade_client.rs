@@ -0,0 +1,116 @@
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::{Client, Url};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::service::endpoints::Endpoints;
+use crate::service::rate_limit::RateLimiter;
+
+const SESSION_COOKIE: &str = "POESESSID";
+
+// A hung request fails after this long instead of leaving the price check spinning
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// The session id is kept in the OS keyring rather than next to our other settings
+const KEYRING_SERVICE: &str = "com.makestuffwithme.chiseled";
+const KEYRING_USER: &str = "poesessid";
+
+/// The HTTP client shared by every trade API request, so connections and cookies are reused
+/// and the trade site's rate limits are tracked across commands
+#[derive(Debug)]
+pub struct TradeClient {
+    pub http: Client,
+    pub limiter: RateLimiter,
+    pub endpoints: Endpoints,
+    cookies: Arc<Jar>,
+}
+
+impl TradeClient {
+    pub fn new(endpoints: Endpoints) -> Result<Self, String> {
+        let cookies = Arc::new(Jar::default());
+        let http = Client::builder()
+            .user_agent("chiseled-price-checker")
+            .cookie_provider(cookies.clone())
+            .timeout(REQUEST_TIMEOUT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        Ok(Self {
+            http,
+            limiter: RateLimiter::default(),
+            endpoints,
+            cookies,
+        })
+    }
+
+    /// Sends the session id with every request, or stops sending one if it's None
+    pub fn set_session_id(&self, session_id: Option<&str>) {
+        let Ok(url) = Url::parse(&self.endpoints.base_url) else {
+            log::warn!("Not sending session id, invalid base URL {}", self.endpoints.base_url);
+            return;
+        };
+        let secure = if url.scheme() == "https" { "; Secure" } else { "" };
+        let cookie = match session_id {
+            Some(session_id) => format!("{}={}; Path=/{}", SESSION_COOKIE, session_id, secure),
+            // An already expired cookie replaces and drops the stored one
+            None => format!("{}=; Path=/; Max-Age=0", SESSION_COOKIE),
+        };
+        self.cookies.add_cookie_str(&cookie, &url);
+    }
+
+    /// Cookies sent to the trade site, for connections made outside the HTTP client
+    pub fn cookie_header(&self) -> Option<String> {
+        let url = Url::parse(&self.endpoints.base_url).ok()?;
+        let cookies = self.cookies.cookies(&url)?;
+        cookies.to_str().ok().map(str::to_string)
+    }
+}
+
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+        .map_err(|e| format!("Failed to open keyring: {}", e))
+}
+
+pub fn load_session_id() -> Result<Option<String>, String> {
+    match keyring_entry()?.get_password() {
+        Ok(session_id) => Ok(Some(session_id)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read session id from keyring: {}", e)),
+    }
+}
+
+/// Saves the session id to the keyring, or removes it if it's None
+pub fn store_session_id(session_id: Option<&str>) -> Result<(), String> {
+    let entry = keyring_entry()?;
+    match session_id {
+        Some(session_id) => entry
+            .set_password(session_id)
+            .map_err(|e| format!("Failed to save session id to keyring: {}", e)),
+        None => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to remove session id from keyring: {}", e)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_cookie() {
+        let client = TradeClient::new(Endpoints::default()).expect("Should create client");
+        let url = Url::parse("https://www.pathofexile.com/api/trade2/search/poe2/Standard").unwrap();
+        assert!(client.cookies.cookies(&url).is_none());
+
+        client.set_session_id(Some("abc123"));
+        let cookies = client.cookies.cookies(&url).expect("Should send the session cookie");
+        assert_eq!(cookies.to_str().unwrap(), "POESESSID=abc123");
+        assert_eq!(client.cookie_header().as_deref(), Some("POESESSID=abc123"));
+
+        client.set_session_id(None);
+        assert!(client.cookies.cookies(&url).is_none());
+    }
+}
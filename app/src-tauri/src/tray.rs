@@ -31,6 +31,14 @@ async fn handle_update(app: &AppHandle) {
     }
 }
 
+async fn handle_refresh(app: &AppHandle) {
+    match crate::reload_mappings(app).await {
+        Ok(true) => show_dialog(app, "Trade Data Refreshed", "Loaded the latest mods and base types."),
+        Ok(false) => show_dialog(app, "Trade Data Refreshed", "Trade data is already up to date."),
+        Err(e) => show_dialog(app, "Error", &format!("Failed to refresh trade data: {}", e)),
+    }
+}
+
 fn show_dialog(app: &AppHandle, title: &str, message: &str) {
     if let Some(window) = app.get_webview_window("main") {
         let _ = window
@@ -43,9 +51,10 @@ fn show_dialog(app: &AppHandle, title: &str, message: &str) {
 
 pub fn create_tray_menu(app: &AppHandle) -> TrayIcon {
     let show_item = MenuItem::with_id(app, "show", "Show", true, None::<&str>).unwrap();
+    let refresh_item = MenuItem::with_id(app, "refresh", "Refresh Trade Data", true, None::<&str>).unwrap();
     let update_item = MenuItem::with_id(app, "update", "Update", true, None::<&str>).unwrap();
     let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>).unwrap();
-    let menu = Menu::with_items(app, &[&show_item, &update_item, &quit_item]).unwrap();
+    let menu = Menu::with_items(app, &[&show_item, &refresh_item, &update_item, &quit_item]).unwrap();
 
     let tray = TrayIconBuilder::new()
         .menu(&menu)
@@ -61,6 +70,12 @@ pub fn create_tray_menu(app: &AppHandle) -> TrayIcon {
                         let _ = window.set_focus();
                     }
                 }
+                "refresh" => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        handle_refresh(&app).await;
+                    });
+                }
                 "update" => {
                     tauri::async_runtime::block_on(handle_update(app));
                 }
@@ -0,0 +1,96 @@
+// Mirrors TradeError in src-tauri/src/error.rs
+export type TradeError =
+    | { kind: 'no_results' }
+    | { kind: 'rate_limited'; retry_after: number }
+    | { kind: 'invalid_query'; code: number; message: string }
+    | { kind: 'not_logged_in'; status: number; message: string }
+    | { kind: 'api'; code: number | null; status: number; message: string }
+    | { kind: 'network'; request: string; message: string }
+    | { kind: 'timed_out'; request: string }
+    | { kind: 'cancelled' }
+    | { kind: 'invalid_response'; message: string }
+    | { kind: 'invalid_item_text'; message: string }
+    | { kind: 'other'; message: string };
+
+export interface ErrorDisplay {
+    message: string;
+    guidance: string | null;
+    // Whether running the same request again could succeed
+    retryable: boolean;
+}
+
+function isTradeError(err: unknown): err is TradeError {
+    return typeof err === 'object' && err !== null && 'kind' in err;
+}
+
+// A newer search replaced the failed one, so there's nothing to show
+export function isCancelled(err: unknown): boolean {
+    return isTradeError(err) && err.kind === 'cancelled';
+}
+
+export function describeError(err: unknown): ErrorDisplay {
+    if (!isTradeError(err)) {
+        return { message: err instanceof Error ? err.message : String(err), guidance: null, retryable: false };
+    }
+
+    switch (err.kind) {
+        case 'no_results':
+            return {
+                message: 'No results found',
+                guidance: 'Try loosening or disabling some filters.',
+                retryable: false
+            };
+        case 'rate_limited':
+            return {
+                message: `Trade site rate limit reached, try again in ${err.retry_after}s`,
+                guidance: null,
+                retryable: true
+            };
+        case 'invalid_query':
+            return {
+                message: `Invalid query: ${err.message}`,
+                guidance: 'The trade site rejected the search. Check the stat filters and values.',
+                retryable: false
+            };
+        case 'not_logged_in':
+            return {
+                message: `Not logged in: ${err.message}`,
+                guidance: 'Log in to the official trade site, or save a fresh POESESSID.',
+                retryable: false
+            };
+        case 'api':
+            return {
+                message: `Trade site error (${err.status}): ${err.message}`,
+                guidance: null,
+                retryable: err.status >= 500
+            };
+        case 'network':
+            return {
+                message: `${err.request} request failed: ${err.message}`,
+                guidance: 'Check your connection to the trade site.',
+                retryable: true
+            };
+        case 'timed_out':
+            return {
+                message: `${err.request} request timed out`,
+                guidance: 'The trade site is slow to respond right now.',
+                retryable: true
+            };
+        case 'cancelled':
+            return { message: 'Search cancelled', guidance: null, retryable: false };
+        case 'invalid_response':
+            return {
+                message: `Unexpected response from the trade site: ${err.message}`,
+                guidance: null,
+                retryable: true
+            };
+        case 'invalid_item_text':
+            return {
+                message: `Failed to parse item text: ${err.message}`,
+                guidance: 'Copy the item again with Ctrl+C in game.',
+                retryable: false
+            };
+        case 'other':
+            return { message: err.message, guidance: null, retryable: false };
+    }
+}
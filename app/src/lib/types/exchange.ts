@@ -0,0 +1,17 @@
+// Mirrors ExchangeOffer in src-tauri/src/model/exchange.rs
+export interface ExchangeOffer {
+    id: string;
+    account: {
+        name: string;
+        online?: {
+            league: string;
+            status?: string;
+        };
+    };
+    indexed: string;
+    pay: { currency: string; amount: number };
+    get: { currency: string; amount: number };
+    stock: number;
+    ratio: number;
+    whisper?: string;
+}
@@ -19,6 +19,18 @@ export interface StatFilter {
     text: string;
     enabled: boolean;
     value: StatValue;
+    weight: number | null;
+    // Option of a stat imported from the trade site
+    option?: unknown;
+}
+
+export type StatGroupType = 'and' | 'not' | 'count' | 'weight';
+
+export interface StatGroup {
+    type: StatGroupType;
+    filters: StatFilter[];
+    value: StatValue;
+    enabled: boolean;
 }
 
 export interface PriceFilter {
@@ -28,8 +40,16 @@ export interface PriceFilter {
     max: number | null;
 }
 
-export interface ToggleFilter {
-    enabled: boolean;
+export interface OptionFilter {
+    option: string;
+}
+
+export type SortKey = 'price' | 'dps' | 'pdps' | 'edps' | 'stat' | 'item_level';
+
+export interface SortFilter {
+    key: SortKey;
+    stat_id: string | null;
+    direction: 'asc' | 'desc';
 }
 
 export interface League {
@@ -44,7 +64,8 @@ export interface FilterConfig {
     rangeFilter?: RangeFilter;
     textFilter?: TextFilter;
     priceFilter?: PriceFilter;
-    toggleFilter?: ToggleFilter;
+    optionFilter?: OptionFilter;
+    sortFilter?: SortFilter;
     options?: { value: string; label: string }[];
     readonly?: boolean;
     onChange?: (value: string) => void;
@@ -62,6 +83,7 @@ export interface TradeFilters {
     total_dps: RangeFilter | null;
     attack_speed: RangeFilter | null;
     critical_chance: RangeFilter | null;
+    reload_time: RangeFilter | null;
     socket_count: RangeFilter | null;
     armour: RangeFilter | null;
     energy_shield: RangeFilter | null;
@@ -74,7 +96,16 @@ export interface TradeFilters {
     explicit_mods: StatFilter[];
     implicit_mods: StatFilter[];
     rune_mods: StatFilter[];
+    stat_groups: StatGroup[];
     price: PriceFilter;
-    online_only: ToggleFilter;
+    status: OptionFilter;
+    sale_type: OptionFilter;
+    listed_within: OptionFilter;
+    account: TextFilter | null;
     league: TextFilter | null;
+    // Realm of the league, null for the default one
+    realm: TextFilter | null;
+    sort: SortFilter;
+
+    unique_candidates: string[];
 } 
\ No newline at end of file
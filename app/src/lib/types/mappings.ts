@@ -0,0 +1,5 @@
+// Mirrors MappingsStatus in src-tauri/src/lib.rs
+export type MappingsStatus =
+    | { state: 'loading' }
+    | { state: 'ready' }
+    | { state: 'failed'; error: string };
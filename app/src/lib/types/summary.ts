@@ -0,0 +1,10 @@
+// Mirrors PriceSummary in src-tauri/src/model/price_summary.rs
+export interface PriceSummary {
+    currency: string;
+    min: number;
+    lower_quartile: number;
+    median: number;
+    upper_quartile: number;
+    sampled: number;
+    currencies: { currency: string; count: number }[];
+}